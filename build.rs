@@ -0,0 +1,66 @@
+//! Copy the admin panel config read in production to `OUT_DIR`, it's embedded in the binary
+//! from there. JSON schemas and overlays of the other environments are left out.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Root folder of the admin panel config
+const CONFIG_ROOT: &str = "pro_admin";
+
+/// Files of the site and dashboard config, in any of the supported formats
+const FILES: [&str; 2] = ["config", "dashboard"];
+
+const EXTENSIONS: [&str; 4] = ["toml", "yaml", "yml", "json"];
+
+/// Folders of the table and dashboard config, and the overlay of the production environment
+const FOLDERS: [&str; 4] = [
+    "dashboards",
+    "raw_tables",
+    "composite_tables",
+    "overlays/production",
+];
+
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed={CONFIG_ROOT}");
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let target = out_dir.join(CONFIG_ROOT);
+    // Files deleted since the last build are left out as well
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::create_dir_all(&target)?;
+
+    let root = Path::new(CONFIG_ROOT);
+    for name in FILES {
+        for extension in EXTENSIONS {
+            let file = format!("{name}.{extension}");
+            if root.join(&file).is_file() {
+                fs::copy(root.join(&file), target.join(&file))?;
+            }
+        }
+    }
+    for folder in FOLDERS {
+        copy_dir(&root.join(folder), &target.join(folder))?;
+    }
+    Ok(())
+}
+
+/// Copy the folder along with its sub-folders, if it exists
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
@@ -21,6 +21,7 @@ mod m20250527_054828_create_bakery_table;
 mod m20250527_070516_create_baker_table;
 mod m20250527_070516_create_cake_baker_table;
 mod m20250527_070516_create_cake_table;
+mod m20250601_000001_create_saved_filter_table;
 
 pub struct Migrator;
 
@@ -44,6 +45,7 @@ impl MigratorTrait for Migrator {
             Box::new(m20250527_070516_create_baker_table::Migration),
             Box::new(m20250527_070516_create_cake_table::Migration),
             Box::new(m20250527_070516_create_cake_baker_table::Migration),
+            Box::new(m20250601_000001_create_saved_filter_table::Migration),
         ]
     }
 }
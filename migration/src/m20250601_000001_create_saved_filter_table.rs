@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedFilter::Table)
+                    .col(pk_auto(SavedFilter::Id))
+                    .col(integer(SavedFilter::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-saved_filter-user_id")
+                            .from(SavedFilter::Table, SavedFilter::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(string(SavedFilter::TableName))
+                    .col(string(SavedFilter::Name))
+                    .col(json(SavedFilter::Conditions))
+                    .col(date_time(SavedFilter::CreatedAt).default(Expr::current_timestamp()))
+                    .col(date_time(SavedFilter::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-saved_filter-user_id-table_name-name")
+                    .table(SavedFilter::Table)
+                    .col(SavedFilter::UserId)
+                    .col(SavedFilter::TableName)
+                    .col(SavedFilter::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SavedFilter::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SavedFilter {
+    Table,
+    Id,
+    UserId,
+    TableName,
+    Name,
+    Conditions,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,396 @@
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::{
+    config::{
+        Aggregate, ChartQueryCfg, ChartValueCfg, DrillDownCfg, FilterConditionCfg, FilterOp,
+        TimeBucket,
+    },
+    expr::ColumnExpr,
+    input::parse_value,
+    meta::{ColumnKind, ColumnMeta, EntityMeta, RelationMeta},
+};
+
+/// Error of a chart query, at the path of the offending value in the query, e.g. `key.column`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChartQueryError {
+    /// Path of the value in the query
+    pub path: String,
+    /// Description of the error
+    pub message: String,
+}
+
+impl ChartQueryError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl FromStr for TimeBucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hour" => Ok(Self::Hour),
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "quarter" => Ok(Self::Quarter),
+            "year" => Ok(Self::Year),
+            _ => Err(format!(
+                "`{s}` is not a time scale, i.e. `hour`, `day`, `week`, `month`, `quarter` or `year`"
+            )),
+        }
+    }
+}
+
+impl ChartQueryCfg {
+    /// Relations of the table to join
+    pub fn join_relations<'a>(
+        &self,
+        entity: &'a EntityMeta,
+    ) -> Result<Vec<&'a RelationMeta>, String> {
+        self.joins
+            .iter()
+            .map(|name| match entity.relation(name) {
+                Some(relation) if relation.via.is_none() => Ok(relation),
+                Some(_) => Err(format!(
+                    "relation `{name}` goes through another table and can't be joined"
+                )),
+                None => Err(format!(
+                    "relation `{name}` does not exist on table `{}`",
+                    entity.table_name
+                )),
+            })
+            .collect()
+    }
+
+    /// Table and column referred to as `column` of the table or `table.column` of a joined table
+    pub fn resolve_column<'a>(
+        &self,
+        entities: &'a [EntityMeta],
+        field: &str,
+    ) -> Result<(&'a EntityMeta, &'a ColumnMeta), String> {
+        let find = |table: &str| entities.iter().find(|entity| entity.table_name == table);
+        let (table, column) = field.split_once('.').unwrap_or((&self.table, field));
+        let joined = table == self.table
+            || find(&self.table).is_some_and(|entity| {
+                self.joins.iter().any(|name| {
+                    entity
+                        .relation(name)
+                        .is_some_and(|relation| relation.to_table == table)
+                })
+            });
+        let entity = match find(table) {
+            Some(entity) if joined => entity,
+            Some(_) => return Err(format!("table `{table}` is not joined")),
+            None => return Err(format!("table `{table}` does not exist")),
+        };
+        match entity.column(column) {
+            Some(column) => Ok((entity, column)),
+            None => Err(format!(
+                "column `{column}` does not exist on table `{table}`"
+            )),
+        }
+    }
+
+    /// Values of the chart with the name of their series, a single unnamed value without series
+    pub fn values(&self) -> Vec<(Option<&str>, &ChartValueCfg)> {
+        if self.series.is_empty() {
+            vec![(None, &self.value)]
+        } else {
+            self.series
+                .iter()
+                .map(|series| (Some(series.name.as_str()), &series.value))
+                .collect()
+        }
+    }
+
+    /// Check the tables, relations and columns exist and fit their use in the query
+    pub fn check(&self, entities: &[EntityMeta]) -> Result<(), ChartQueryError> {
+        let Some(entity) = entities
+            .iter()
+            .find(|entity| entity.table_name == self.table)
+        else {
+            return Err(ChartQueryError::new(
+                "table",
+                format!("table `{}` does not exist", self.table),
+            ));
+        };
+        self.join_relations(entity)
+            .map_err(|message| ChartQueryError::new("joins", message))?;
+
+        if let Some(key) = &self.key {
+            let (_, column) = self
+                .resolve_column(entities, &key.column)
+                .map_err(|message| ChartQueryError::new("key.column", message))?;
+            if key.bucket.is_some() && !is_date(column) {
+                return Err(ChartQueryError::new(
+                    "key.bucket",
+                    format!("`{}` is not a date column", key.column),
+                ));
+            }
+        }
+
+        if !self.series.is_empty() && self.value != ChartValueCfg::default() {
+            return Err(ChartQueryError::new(
+                "value",
+                "values are given by the series, leave out `value`",
+            ));
+        }
+        for (i, series) in self.series.iter().enumerate() {
+            let path = format!("series[{i}]");
+            if series.name.is_empty() {
+                return Err(ChartQueryError::new(path, "series needs a name"));
+            }
+            if self.series[..i].iter().any(|s| s.name == series.name) {
+                return Err(ChartQueryError::new(
+                    path,
+                    format!("series `{}` is defined twice", series.name),
+                ));
+            }
+        }
+        for (i, (_, value)) in self.values().into_iter().enumerate() {
+            let path = if self.series.is_empty() {
+                "value".to_owned()
+            } else {
+                format!("series[{i}]")
+            };
+            self.check_value(entities, entity, value)
+                .map_err(|(field, message)| match field {
+                    Some(field) => ChartQueryError::new(format!("{path}.{field}"), message),
+                    None => ChartQueryError::new(path, message),
+                })?;
+        }
+
+        for (i, filter) in self.filters.iter().enumerate() {
+            let path = format!("filters[{i}]");
+            let (entity, column) = self
+                .resolve_column(entities, &filter.field)
+                .map_err(|message| ChartQueryError::new(&path, message))?;
+            // Condition on the column of the joined table
+            let filter = FilterConditionCfg {
+                field: column.name.clone(),
+                ..filter.clone()
+            };
+            filter
+                .check(entity)
+                .map_err(|message| ChartQueryError::new(&path, message))?;
+        }
+
+        if let Some(date_column) = &self.date_column {
+            let (_, column) = self
+                .resolve_column(entities, date_column)
+                .map_err(|message| ChartQueryError::new("date_column", message))?;
+            if !is_date(column) {
+                return Err(ChartQueryError::new(
+                    "date_column",
+                    format!("`{date_column}` is not a date column"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Error of the value, at the offending field of the value if any
+    fn check_value(
+        &self,
+        entities: &[EntityMeta],
+        entity: &EntityMeta,
+        value: &ChartValueCfg,
+    ) -> Result<(), (Option<&'static str>, String)> {
+        match (&value.column, &value.expr) {
+            (Some(_), Some(_)) => {
+                Err((None, "value is either a column or an expression".to_owned()))
+            }
+            (Some(column), None) => self
+                .resolve_column(entities, column)
+                .map(|_| ())
+                .map_err(|message| (Some("column"), message)),
+            (None, Some(expr)) => {
+                let expr =
+                    ColumnExpr::parse(expr).map_err(|err| (Some("expr"), err.to_string()))?;
+                match expr
+                    .columns()
+                    .into_iter()
+                    .find(|c| entity.column(c).is_none())
+                {
+                    Some(column) => Err((
+                        Some("expr"),
+                        format!("column `{column}` does not exist on table `{}`", self.table),
+                    )),
+                    None => Ok(()),
+                }
+            }
+            (None, None) if value.aggregate != Aggregate::Count => {
+                Err((None, "aggregate needs a column or an expression".to_owned()))
+            }
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Values of a data point the drill-down filters refer to, e.g. `{key}`
+const PLACEHOLDERS: [&str; 4] = ["key", "series", "start", "end"];
+
+/// Data point of a chart, as referred to by the drill-down filters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrillDownPoint<'a> {
+    /// Key of the point
+    pub key: &'a str,
+    /// Series of the point, on charts of named series
+    pub series: Option<&'a str>,
+    /// Start of the bucket of dates of the key, e.g. `2024-06-01 00:00:00`
+    pub start: Option<&'a str>,
+    /// Start of the bucket after the one of the key
+    pub end: Option<&'a str>,
+}
+
+impl DrillDownPoint<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "key" => Some(self.key),
+            "series" => self.series,
+            "start" => self.start,
+            "end" => self.end,
+            _ => None,
+        }
+    }
+}
+
+impl DrillDownCfg {
+    /// Filters of the rows behind a data point, with the placeholders of the point. The filters
+    /// given, or else the key column of the query if it's on the table: by the bucket of dates
+    /// on a date column and by the key on other columns. Empty if there's nothing to filter by.
+    pub fn templates(
+        &self,
+        entities: &[EntityMeta],
+        query: Option<&ChartQueryCfg>,
+    ) -> Vec<FilterConditionCfg> {
+        if !self.filters.is_empty() {
+            return self.filters.clone();
+        }
+        let Some((query, key)) = query.and_then(|query| Some((query, query.key.as_ref()?))) else {
+            return Vec::new();
+        };
+        let column = match query.resolve_column(entities, &key.column) {
+            Ok((entity, column)) if entity.table_name == self.table => column,
+            _ => return Vec::new(),
+        };
+        let condition = |op, value: &str| FilterConditionCfg {
+            field: column.name.clone(),
+            op,
+            value: value.into(),
+        };
+        if is_date(column) {
+            vec![
+                condition(FilterOp::Gte, "{start}"),
+                condition(FilterOp::Lt, "{end}"),
+            ]
+        } else {
+            vec![condition(FilterOp::Eq, "{key}")]
+        }
+    }
+
+    /// Check the table and the columns exist and the filters refer to values of the data point
+    pub fn check(
+        &self,
+        entities: &[EntityMeta],
+        query: Option<&ChartQueryCfg>,
+    ) -> Result<(), ChartQueryError> {
+        let Some(entity) = entities
+            .iter()
+            .find(|entity| entity.table_name == self.table)
+        else {
+            return Err(ChartQueryError::new(
+                "table",
+                format!("table `{}` does not exist", self.table),
+            ));
+        };
+        if self.templates(entities, query).is_empty() {
+            return Err(ChartQueryError::new(
+                "filters",
+                format!(
+                    "filters are needed, the key of the chart is not a column of table `{}`",
+                    self.table
+                ),
+            ));
+        }
+        for (i, filter) in self.filters.iter().enumerate() {
+            let path = format!("filters[{i}]");
+            filter
+                .check(entity)
+                .map_err(|message| ChartQueryError::new(&path, message))?;
+            if let Some(name) =
+                placeholders(&filter.value).find(|name| !PLACEHOLDERS.contains(name))
+            {
+                return Err(ChartQueryError::new(
+                    &path,
+                    format!(
+                        "`{{{name}}}` is not a value of the data point, i.e. `{{key}}`, `{{series}}`, `{{start}}` or `{{end}}`"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FilterConditionCfg {
+    /// Condition on the rows behind the data point, with the placeholders of the value filled in.
+    /// A value of a single placeholder is taken as a value of the column, e.g. a number or a date
+    /// without the time. `None` if the point has no such value or it doesn't fit the column.
+    pub fn fill(&self, entity: &EntityMeta, point: &DrillDownPoint) -> Option<Self> {
+        let column = entity.column(&self.field)?;
+        Some(Self {
+            value: fill_value(&self.value, column, point)?,
+            ..self.clone()
+        })
+    }
+}
+
+fn fill_value(value: &Value, column: &ColumnMeta, point: &DrillDownPoint) -> Option<Value> {
+    match value {
+        Value::String(text) => {
+            let name = text.strip_prefix('{').and_then(|s| s.strip_suffix('}'));
+            if let Some(name) = name.filter(|name| !name.contains(['{', '}'])) {
+                let value = point.get(name)?;
+                return match column.kind {
+                    ColumnKind::Date => Some(value.split(' ').next().unwrap_or(value).into()),
+                    _ => parse_value(column, value),
+                };
+            }
+            let mut filled = text.clone();
+            for name in placeholders(value) {
+                filled = filled.replace(&format!("{{{name}}}"), point.get(name)?);
+            }
+            Some(filled.into())
+        }
+        Value::Array(values) => values
+            .iter()
+            .map(|value| fill_value(value, column, point))
+            .collect::<Option<_>>()
+            .map(Value::Array),
+        value => Some(value.clone()),
+    }
+}
+
+/// Names of the placeholders in the value, e.g. `key` of `{key}`
+fn placeholders(value: &Value) -> Box<dyn Iterator<Item = &str> + '_> {
+    match value {
+        Value::String(text) => Box::new(
+            text.split('{')
+                .skip(1)
+                .filter_map(|part| part.split_once('}').map(|(name, _)| name)),
+        ),
+        Value::Array(values) => Box::new(values.iter().flat_map(placeholders)),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+fn is_date(column: &ColumnMeta) -> bool {
+    matches!(column.kind, ColumnKind::Date | ColumnKind::DateTime)
+}
@@ -1,35 +1,94 @@
 use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::{fmt::Debug, path::PathBuf};
+
+use crate::date_range::DateRange;
 
 /// Admin panel config
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JsonCfg {
     /// Site config
     pub site: SiteCfg,
     /// Dashboard config
     #[serde(default)]
     pub dashboard: DashboardCfg,
+    /// Named dashboards besides the default one, loaded from `dashboards/`
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub dashboards: IndexMap<String, DashboardCfg>,
     /// Raw table config
     #[serde(default)]
     pub raw_tables: IndexMap<String, RawTableCfg>,
     /// Composite table config
     #[serde(default)]
     pub composite_tables: IndexMap<String, CompositeTableCfg>,
+    /// Where the table config are loaded from
+    #[serde(skip)]
+    pub sources: ConfigSources,
+}
+
+/// Source of each table config, for error reporting
+#[derive(Debug, Default, Clone)]
+pub struct ConfigSources {
+    /// Path of the file of the site config, none if it's the default config
+    pub site: Option<PathBuf>,
+    /// Source of raw table config
+    pub raw_tables: IndexMap<String, TableSource>,
+    /// Source of composite table config
+    pub composite_tables: IndexMap<String, TableSource>,
+    /// Source of named dashboards
+    pub dashboards: IndexMap<String, TableSource>,
+}
+
+impl ConfigSources {
+    /// File of the site config, `config.toml` if it's the default config
+    pub fn site_file(&self) -> String {
+        match &self.site {
+            Some(path) => path.display().to_string(),
+            None => "config.toml".to_owned(),
+        }
+    }
+}
+
+/// Source of a table config
+#[derive(Debug, Clone)]
+pub struct TableSource {
+    /// Path of the config file
+    pub file: PathBuf,
+    /// Path of the table config inside the file, empty if the file contains only this table
+    pub path: String,
+}
+
+impl TableSource {
+    /// Table config stored in its own file
+    pub fn file(file: PathBuf) -> Self {
+        Self {
+            file,
+            path: String::new(),
+        }
+    }
+
+    /// Table config stored under `path` of a bundled config file
+    pub fn bundle(file: PathBuf, path: String) -> Self {
+        Self { file, path }
+    }
 }
 
 /// Site config
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SiteCfg {
     /// Theme config
     pub theme: ThemeCfg,
     /// Menu config
     #[serde(default)]
     pub menu: MenuCfg,
+    /// Timezone the dates of the dashboards are relative to, e.g. `Asia/Shanghai`, UTC if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
 }
 
 /// Theme config
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ThemeCfg {
     /// Title of admin panel
     pub title: String,
@@ -40,7 +99,7 @@ pub struct ThemeCfg {
 }
 
 /// Menu config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct MenuCfg {
     /// Dashboard
     pub dashboard: MenuItemCfg,
@@ -48,10 +107,44 @@ pub struct MenuCfg {
     pub raw_table: MenuItemCfg,
     /// Composite Table
     pub composite_table: MenuItemCfg,
+    /// Menu tree replacing the default menu, entries are shown in the order listed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<MenuNodeCfg>,
+}
+
+/// Entry of the menu tree, either a group of entries or a link to a page,
+/// e.g. `{ raw_table = "customer", icon = "UserOutlined" }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MenuNodeCfg {
+    /// Title in the menu, defaults to the title of the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Name of an Ant Design icon, e.g. `ShoppingCartOutlined`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Link to the dashboard of the name, `default` is the one in `dashboard.toml`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dashboard: Option<String>,
+    /// Link to the raw table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_table: Option<String>,
+    /// Link to the composite table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub composite_table: Option<String>,
+    /// External link, opened in a new tab
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Entries of the group
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MenuNodeCfg>,
+    /// Only shown to users with one of the roles, shown to everyone if empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
 }
 
 /// Menu item config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct MenuItemCfg {
     /// Title of menu item
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -62,63 +155,311 @@ pub struct MenuItemCfg {
 }
 
 /// Dashboard config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardCfg {
+    /// Title of info card section
     pub title: String,
+    /// Subtitle of info card section
     #[serde(default)]
     pub subtitle: String,
+    /// Info card section
     #[serde(default)]
     pub info: Option<DashboardInfo>,
+    /// Chart grid
     #[serde(default)]
     pub row: Vec<DashboardRow>,
+    /// Only available to users with one of the roles, available to everyone if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+}
+
+/// Name of the dashboard in `dashboard.toml`
+pub const DEFAULT_DASHBOARD: &str = "default";
+
+impl JsonCfg {
+    /// Dashboard of the name, the default dashboard if no name is given
+    pub fn find_dashboard(&self, name: Option<&str>) -> Option<&DashboardCfg> {
+        match name {
+            None | Some(DEFAULT_DASHBOARD) => Some(&self.dashboard),
+            Some(name) => self.dashboards.get(name),
+        }
+    }
+}
+
+impl DashboardCfg {
+    /// Charts on the dashboard
+    pub fn charts(&self) -> impl Iterator<Item = &DashboardChart> {
+        self.row
+            .iter()
+            .flat_map(|row| row.col.iter())
+            .filter_map(|col| col.chart.as_ref())
+    }
+
+    /// Chart of the key, if it's on the dashboard
+    pub fn find_chart(&self, chart: &str) -> Option<&DashboardChart> {
+        self.charts().find(|c| c.chart == chart)
+    }
+
+    /// Charts on the dashboard, to be edited
+    pub fn charts_mut(&mut self) -> impl Iterator<Item = &mut DashboardChart> {
+        self.row
+            .iter_mut()
+            .flat_map(|row| row.col.iter_mut())
+            .filter_map(|col| col.chart.as_mut())
+    }
 }
 
 /// Dashboard info
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardInfo {
+    /// Info cards
     pub card: Vec<DashboardInfoCard>,
 }
 
 /// Dashboard info card
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardInfoCard {
+    /// Title of info card
     #[serde(default)]
     pub title: String,
+    /// Description of info card
     #[serde(default)]
     pub description: String,
+    /// Learn more link of info card
     #[serde(default)]
     pub link: String,
 }
 
 /// Dashboard panel row
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardRow {
+    /// Row gutter
     pub gutter: u32,
+    /// Columns of the row
     pub col: Vec<DashboardRowCol>,
 }
 
 /// Dashboard panel column
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardRowCol {
+    /// Column span, a row spans 24
     pub span: u32,
+    /// Title of the chart
     pub title: String,
+    /// Chart in the column
     pub chart: Option<DashboardChart>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Dashboard chart
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardChart {
-    chart: String,
-    kind: String,
+    /// An unique key for fetching chart data
+    pub chart: String,
+    /// Kind of chart
+    pub kind: ChartKind,
+    /// Time scale of datetime picker
     timescale: Option<String>,
+    /// Default start of datetime
     from_date: Option<String>,
+    /// Default end of datetime
     to_date: Option<String>,
+    /// Default dynamic date range based on current date
     default_date_range: Option<String>,
+    /// Title of X axis
     x_axis_title: Option<String>,
+    /// Title of Y axis
     y_axis_title: Option<String>,
+    /// Title of the secondary Y axis
+    y2_axis_title: Option<String>,
+    /// Series drawn against the secondary Y axis, e.g. `["revenue"]` next to the number of orders
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_axis: Vec<String>,
+    /// Seconds the chart data is cached for, e.g. `300`, computed on every request if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    /// Table opened filtered to a data point when it's clicked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drill_down: Option<DrillDownCfg>,
+    /// Query of the chart data, charts without a query are computed by the backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<ChartQueryCfg>,
+}
+
+impl DashboardChart {
+    /// Time scale of the date picker, e.g. `day` or `month`
+    pub fn timescale(&self) -> Option<&str> {
+        self.timescale.as_deref()
+    }
+
+    /// Time scale of the date picker as a bucket of dates, if it's one
+    pub fn time_bucket(&self) -> Option<Result<TimeBucket, String>> {
+        self.timescale.as_deref().map(str::parse)
+    }
+
+    /// Default date range, relative to the current date
+    pub fn default_date_range(&self) -> Option<Result<DateRange, String>> {
+        self.default_date_range.as_deref().map(str::parse)
+    }
+
+    /// Default start and end of the date picker
+    pub fn default_dates(&self) -> (Option<&str>, Option<&str>) {
+        (self.from_date.as_deref(), self.to_date.as_deref())
+    }
+
+    /// Set the default start and end of the date picker, e.g. from the default date range
+    pub fn set_default_dates(&mut self, from: String, to: String) {
+        self.from_date = Some(from);
+        self.to_date = Some(to);
+    }
+}
+
+/// Kind of chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    Line,
+    Area,
+    Bar,
+    /// Bars of the series stacked on each other
+    StackedBar,
+    Pie,
+    /// Points of the value against the key
+    Scatter,
+    /// Single figure, e.g. the revenue of the month
+    Stat,
+    /// Rows of the data
+    Table,
+}
+
+/// Query of a chart, rows of the table are grouped by the key and a value is aggregated per group.
+/// Columns of a joined table are referred to as `table.column`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChartQueryCfg {
+    /// Table the rows are selected from
+    pub table: String,
+    /// Relations of the table to join, e.g. `["sales_order_header"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub joins: Vec<String>,
+    /// Key of the groups, all rows are aggregated into a single value without a key, as in a `stat`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<ChartKeyCfg>,
+    /// Value of each group, the number of rows by default
+    #[serde(default)]
+    pub value: ChartValueCfg,
+    /// Named series of values in place of the single `value`, e.g. orders and revenue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<ChartSeriesCfg>,
+    /// Conditions on the rows
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<FilterConditionCfg>,
+    /// Date column limited to the date range picked on the chart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_column: Option<String>,
+    /// Sort the groups, by key if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<ChartOrderByCfg>,
+    /// Maximum number of groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Key of the chart groups
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChartKeyCfg {
+    /// Column grouped by
+    pub column: String,
+    /// Group dates by hour, day, week, month, quarter or year, e.g. `order_date` by `month`,
+    /// dates are grouped by the time scale of the chart if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<TimeBucket>,
+    /// Order of the keys, e.g. the stages of a funnel, keys not listed come after
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+}
+
+/// Span of time dates are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBucket {
+    Hour,
+    Day,
+    /// ISO week, starting on Monday
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+/// Value of a chart group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ChartValueCfg {
+    /// Aggregate function
+    pub aggregate: Aggregate,
+    /// Column aggregated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Expression over the columns of the table aggregated, as in virtual columns,
+    /// e.g. `unit_price * order_qty`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expr: Option<String>,
+}
+
+/// Named series of a chart
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChartSeriesCfg {
+    /// Name of the series
+    pub name: String,
+    /// Value of the series in each group
+    #[serde(flatten)]
+    pub value: ChartValueCfg,
+}
+
+/// Aggregate function
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    /// Number of rows, or of values that aren't null if a column is given
+    #[default]
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// Sorter of the chart groups
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChartOrderByCfg {
+    /// Sort by `key` or `value`
+    pub field: ChartField,
+    /// Sort in ASC / DESC direction
+    pub order: Order,
+}
+
+/// Field of the chart data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartField {
+    Key,
+    Value,
+}
+
+/// Drill-down of the data points of a chart into the rows of a raw table
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DrillDownCfg {
+    /// Raw table opened on a click on a data point
+    pub table: String,
+    /// Filter of the rows behind a data point, values refer to the point as `{key}` and `{series}`
+    /// and to the bucket of dates of the key as `{start}` and `{end}`, the start of the next bucket,
+    /// e.g. `[{ field = "created_date", op = "gte", value = "{start}" }]`.
+    /// Rows are filtered by the key column of the chart query if not given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<FilterConditionCfg>,
 }
 
 /// Composite table config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct CompositeTableCfg {
     /// Parent table config
     pub parent: ParentTableCfg,
@@ -127,7 +468,7 @@ pub struct CompositeTableCfg {
 }
 
 /// Parent table config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ParentTableCfg {
     /// Name of the parent table
     pub name: String,
@@ -137,7 +478,7 @@ pub struct ParentTableCfg {
 }
 
 /// Child tables config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ChildTableCfg {
     /// Name of the SeaORM relation
     pub relation: String,
@@ -147,12 +488,12 @@ pub struct ChildTableCfg {
 }
 
 /// Raw table config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct RawTableCfg {
     /// Table config
     pub table: TableCfg,
-    /// filter config
+    /// Filter config
     pub filter: FilterCfg,
     /// View config
     pub view: ViewCfg,
@@ -164,16 +505,23 @@ pub struct RawTableCfg {
     pub update: UpdateCfg,
     /// Delete config
     pub delete: DeleteCfg,
+    /// Column permissions by casbin subject, i.e. a role or a user,
+    /// e.g. `[permissions.viewer] hidden_columns = ["rowguid"]`
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub permissions: IndexMap<String, PermissionCfg>,
 }
 
 /// Table config
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct TableCfg {
     /// Show all columns including column not mention in the `columns` config
     pub all_columns: bool,
     /// Column specific config
     pub columns: Vec<ColumnCfg>,
+    /// Read-only columns computed from other columns of the table
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub virtual_columns: Vec<VirtualColumnCfg>,
     /// List of columns that are hidden on the view table
     pub hidden_columns: Vec<String>,
     /// Sorter of the view table
@@ -195,6 +543,7 @@ impl Default for TableCfg {
         TableCfg {
             all_columns: true,
             columns: Vec::new(),
+            virtual_columns: Vec::new(),
             hidden_columns: Vec::new(),
             order_by: None,
             page_size: None,
@@ -205,15 +554,70 @@ impl Default for TableCfg {
 }
 
 /// Filter config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct FilterCfg {
     /// List of columns that are hidden on the filter panel
     pub hidden_columns: Vec<String>,
+    /// Filter applied when the table is opened, e.g. `[{ field = "status", op = "eq", value = 5 }]`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub default: Vec<FilterConditionCfg>,
+    /// Named filters to pick from on the filter panel
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub presets: Vec<FilterPresetCfg>,
+}
+
+/// Filter preset config
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FilterPresetCfg {
+    /// Name of the preset, unique in the table
+    pub name: String,
+    /// Display title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Conditions of the filter
+    pub conditions: Vec<FilterConditionCfg>,
+}
+
+/// Condition on a column, a row has to meet all conditions of a filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FilterConditionCfg {
+    /// Name of the SQL column
+    pub field: String,
+    /// Comparison operator
+    pub op: FilterOp,
+    /// Value to compare with, a list for `is_in`, `is_not_in` and `between`,
+    /// a date range such as `last 30 days` for `last`, none for `is_null` and `is_not_null`
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub value: serde_json::Value,
+}
+
+/// Comparison operator of a filter condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    StartsWith,
+    EndsWith,
+    IsIn,
+    IsNotIn,
+    IsNull,
+    IsNotNull,
+    /// Between two values, inclusive
+    Between,
+    /// Within a date range relative to the current date, see [`DateRange`](crate::DateRange),
+    /// e.g. `last 30 days` or `this month`
+    Last,
 }
 
 /// Display density
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TableSize {
     Large,
@@ -222,7 +626,7 @@ pub enum TableSize {
 }
 
 /// Column specific config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ColumnCfg {
     /// Display title
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -235,18 +639,40 @@ pub struct ColumnCfg {
     /// Column width
     #[serde(skip_serializing_if = "Option::is_none")]
     pub width: Option<usize>,
-    /// Data type
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub input_type: Option<String>,
-    // TODO
-    // pub image_url_prefix: Option<String>,
+    /// Input type, e.g. `"image"` or `{ type = "image", url_prefix = "/uploads/" }`
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "input_type_repr"
+    )]
+    #[schemars(schema_with = "input_type_repr::schema")]
+    pub input_type: Option<InputType>,
     /// Clip long text
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ellipsis: Option<bool>,
+    /// Display the value stored in the column as a label, e.g. `{ 1 = "In Process" }`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<SelectOptions>,
+}
+
+/// Virtual column config
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct VirtualColumnCfg {
+    /// Display title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Name of the virtual column, must not clash with the columns of the table
+    pub field: String,
+    /// Expression over the columns of the table, e.g. `unit_price * order_qty`,
+    /// see [`ColumnExpr`](crate::ColumnExpr) for the syntax
+    pub expr: String,
+    /// Column width
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
 }
 
 /// Editor config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct EditorCfg {
     /// Use editor for this table?
@@ -259,7 +685,7 @@ pub struct EditorCfg {
 }
 
 /// Field specific config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct FieldCfg {
     /// Display title
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -269,20 +695,327 @@ pub struct FieldCfg {
     /// Column span
     #[serde(skip_serializing_if = "Option::is_none")]
     pub span: Option<usize>,
-    /// Data type
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub input_type: Option<String>,
+    /// Input type, e.g. `"textarea"` or `{ type = "money", currency = "USD" }`
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "input_type_repr"
+    )]
+    #[schemars(schema_with = "input_type_repr::schema")]
+    pub input_type: Option<InputType>,
     /// Row length of textarea
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rows: Option<usize>,
+    /// Values accepted by the field and their labels, e.g. `{ 1 = "In Process" }`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<SelectOptions>,
+    /// Validation rules, e.g. `{ required = true, max_length = 50 }`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<RulesCfg>,
+}
+
+/// Validation rules of a field, checked by the form and again on saving
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RulesCfg {
+    /// Value can't be empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// Minimum number of characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    /// Maximum number of characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Minimum value of a number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Maximum value of a number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Regular expression the value must match, e.g. `^[A-Z]{2}-[0-9]+$`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Value must be an email address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<bool>,
+    /// Value must be a URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<bool>,
+    /// Value must not be taken by another row of the table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique: Option<bool>,
+    /// Error message shown in place of the default message of the rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Input type of a column or field, along with the options of the input
+///
+/// Written as the name of the input type, e.g. `"image"`, or a table of the type and
+/// its options, e.g. `{ type = "money", currency = "USD", decimal_places = 2 }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum InputType {
+    /// Single line text
+    Text,
+    /// Multi-line text
+    Textarea,
+    /// Number
+    Number {
+        /// Number of digits after the decimal point
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimal_places: Option<u32>,
+    },
+    /// Amount of money
+    Money {
+        /// ISO 4217 currency code, e.g. `USD`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<String>,
+        /// Number of digits after the decimal point
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimal_places: Option<u32>,
+    },
+    /// Percentage
+    Percent {
+        /// Number of digits after the decimal point
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimal_places: Option<u32>,
+    },
+    /// Date picker
+    Date {
+        /// Display format, e.g. `YYYY-MM-DD`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+    /// Date and time picker
+    Datetime {
+        /// Display format, e.g. `YYYY-MM-DD HH:mm:ss`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+    /// Switch
+    Boolean,
+    /// Pick one of the options
+    Select {
+        /// Options to pick from
+        #[serde(default)]
+        options: SelectOptions,
+    },
+    /// Pick any of the options
+    MultiSelect {
+        /// Options to pick from
+        #[serde(default)]
+        options: SelectOptions,
+    },
+    /// Image, the value is the URL or path of the image
+    Image {
+        /// Prefix prepended to the value to form the image URL
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url_prefix: Option<String>,
+    },
+    /// File, the value is the URL or path of the file
+    File {
+        /// Prefix prepended to the value to form the file URL
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url_prefix: Option<String>,
+    },
+    /// JSON editor
+    Json,
+    /// Markdown editor
+    Markdown,
+    /// Color picker
+    Color,
+    /// Pick a row of the related table
+    RelationPicker {
+        /// Name of the SeaORM relation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        relation: Option<String>,
+        /// Column of the related table displayed in the picker
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label_field: Option<String>,
+    },
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Values of a column and their labels
+///
+/// Written as a list of options, a map of value to label, e.g. `{ 1 = "In Process" }`,
+/// or a reference to the active enum of the column, e.g. `{ enum = "order_status" }`.
+/// Options are resolved to a list once the config is loaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SelectOptions {
+    /// List of options
+    List(Vec<SelectOption>),
+    /// Variants of the active enum of the column
+    Enum {
+        /// Name of the enum in the database
+        #[serde(rename = "enum")]
+        name: String,
+    },
+    /// Map of the value stored in the column to the label
+    Map(IndexMap<String, OptionLabel>),
+}
+
+impl Default for SelectOptions {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+/// Label of a value in the map of options
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum OptionLabel {
+    /// Display label
+    Label(String),
+    /// Display label with a color or a badge
+    Styled {
+        /// Display label
+        label: String,
+        /// Color of the tag, e.g. `green` or `#87d068`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+        /// Display as a badge of the status
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        badge: Option<BadgeStatus>,
+    },
+}
+
+/// Option of a select input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SelectOption {
+    /// Value stored in the column, a string or a number
+    pub value: serde_json::Value,
+    /// Display label
+    pub label: String,
+    /// Color of the tag, e.g. `green` or `#87d068`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Display as a badge of the status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<BadgeStatus>,
+}
+
+/// Status of a badge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeStatus {
+    Success,
+    Processing,
+    Default,
+    Error,
+    Warning,
+}
+
+impl InputType {
+    /// Name of the input type, e.g. `multi-select`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Textarea => "textarea",
+            Self::Number { .. } => "number",
+            Self::Money { .. } => "money",
+            Self::Percent { .. } => "percent",
+            Self::Date { .. } => "date",
+            Self::Datetime { .. } => "datetime",
+            Self::Boolean => "boolean",
+            Self::Select { .. } => "select",
+            Self::MultiSelect { .. } => "multi-select",
+            Self::Image { .. } => "image",
+            Self::File { .. } => "file",
+            Self::Json => "json",
+            Self::Markdown => "markdown",
+            Self::Color => "color",
+            Self::RelationPicker { .. } => "relation-picker",
+        }
+    }
+
+    /// Input type without any options, from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        input_type_repr::from_value(serde_json::json!({ "type": name })).ok()
+    }
+}
+
+/// Input type written as its name or a table with the options, it's serialized
+/// as the name when there are no options, as the frontend expects
+mod input_type_repr {
+    use schemars::{json_schema, Schema, SchemaGenerator};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use serde_json::Value;
+
+    use super::InputType;
+
+    pub(super) fn from_value(value: Value) -> Result<InputType, serde_json::Error> {
+        match value {
+            Value::String(name) => serde_json::from_value(serde_json::json!({ "type": name })),
+            value => serde_json::from_value(value),
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        input_type: &Option<InputType>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let Some(input_type) = input_type else {
+            return serializer.serialize_none();
+        };
+        match serde_json::to_value(input_type) {
+            Ok(Value::Object(object)) if object.len() == 1 => {
+                serializer.serialize_str(input_type.name())
+            }
+            _ => input_type.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<InputType>, D::Error> {
+        match Option::<Value>::deserialize(deserializer)? {
+            Some(value) => from_value(value).map(Some).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    pub fn schema(generator: &mut SchemaGenerator) -> Schema {
+        let names: Vec<_> = super::INPUT_TYPES.to_vec();
+        let input_type = generator.subschema_for::<InputType>();
+        json_schema!({
+            "anyOf": [
+                { "type": "string", "enum": names },
+                input_type,
+            ]
+        })
+    }
+}
+
+/// Names of all input types
+pub const INPUT_TYPES: [&str; 16] = [
+    "text",
+    "textarea",
+    "number",
+    "money",
+    "percent",
+    "date",
+    "datetime",
+    "boolean",
+    "select",
+    "multi-select",
+    "image",
+    "file",
+    "json",
+    "markdown",
+    "color",
+    "relation-picker",
+];
+
+/// View config
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct ViewCfg {}
 
 /// Sorter of the view table
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ViewOrderByCfg {
     /// Sort by which column
     pub field: String,
@@ -291,7 +1024,7 @@ pub struct ViewOrderByCfg {
 }
 
 /// Sort in ASC / DESC direction
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Order {
     Asc,
@@ -299,7 +1032,7 @@ pub enum Order {
 }
 
 /// Create config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct CreateCfg {
     /// Is create allowed for this table?
@@ -309,7 +1042,7 @@ pub struct CreateCfg {
 }
 
 /// Update config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct UpdateCfg {
     /// Is update allowed for this table?
@@ -320,8 +1053,18 @@ pub struct UpdateCfg {
     pub readonly_columns: Vec<String>,
 }
 
+/// Column permissions of a role
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PermissionCfg {
+    /// List of columns the role can't see
+    pub hidden_columns: Vec<String>,
+    /// List of columns the role can see but not edit
+    pub readonly_columns: Vec<String>,
+}
+
 /// Delete config
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct DeleteCfg {
     /// Is delete allowed for this table?
@@ -0,0 +1,165 @@
+use std::str::FromStr;
+
+/// Range of dates relative to the current date, e.g. `last 7 days` or `previous quarter`
+///
+/// Ranges are `today`, `yesterday`, `last N days`, `last N weeks`, `last N months`,
+/// `this month`, `this quarter`, `this year`, `previous month`, `previous quarter`,
+/// `previous year` and `month to date`. Ranges of the last N days, weeks or months end today,
+/// N is from 1 to [`MAX_AMOUNT`](DateRange::MAX_AMOUNT).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateRange {
+    Today,
+    Yesterday,
+    /// Span of time ending today
+    Last {
+        amount: u32,
+        unit: DateUnit,
+    },
+    /// Whole period the current date is in
+    This(DatePeriod),
+    /// Whole period before the one the current date is in
+    Previous(DatePeriod),
+    /// From the first day of the month to today
+    MonthToDate,
+}
+
+/// Unit of the last N days, weeks or months
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateUnit {
+    Day,
+    Week,
+    Month,
+}
+
+/// Calendar period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePeriod {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl DateRange {
+    /// Largest N of the last N days, weeks or months
+    pub const MAX_AMOUNT: u32 = 10_000;
+}
+
+impl FromStr for DateRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.to_lowercase();
+        let words: Vec<_> = lowercase.split_whitespace().collect();
+        let period = |word: &str| match word {
+            "month" => Some(DatePeriod::Month),
+            "quarter" => Some(DatePeriod::Quarter),
+            "year" => Some(DatePeriod::Year),
+            _ => None,
+        };
+        let range = match words.as_slice() {
+            ["today"] => Some(Self::Today),
+            ["yesterday"] => Some(Self::Yesterday),
+            ["month", "to", "date"] | ["month-to-date"] => Some(Self::MonthToDate),
+            ["this", word] => period(word).map(Self::This),
+            ["previous", word] => period(word).map(Self::Previous),
+            ["last", amount, unit] => {
+                let unit = match *unit {
+                    "day" | "days" => Some(DateUnit::Day),
+                    "week" | "weeks" => Some(DateUnit::Week),
+                    "month" | "months" => Some(DateUnit::Month),
+                    _ => None,
+                };
+                match (amount.parse(), unit) {
+                    (Ok(amount), Some(unit)) if (1..=Self::MAX_AMOUNT).contains(&amount) => {
+                        Some(Self::Last { amount, unit })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        range.ok_or_else(|| {
+            format!(
+                "`{s}` is not a date range, e.g. `last 7 days`, `this month` or `previous quarter`"
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<DateRange, String> {
+        s.parse()
+    }
+
+    #[test]
+    fn days_and_periods() {
+        assert_eq!(parse("today"), Ok(DateRange::Today));
+        assert_eq!(parse("yesterday"), Ok(DateRange::Yesterday));
+        assert_eq!(
+            parse("this quarter"),
+            Ok(DateRange::This(DatePeriod::Quarter))
+        );
+        assert_eq!(
+            parse("previous month"),
+            Ok(DateRange::Previous(DatePeriod::Month))
+        );
+        assert_eq!(
+            parse("previous year"),
+            Ok(DateRange::Previous(DatePeriod::Year))
+        );
+        assert_eq!(parse("month to date"), Ok(DateRange::MonthToDate));
+        assert_eq!(parse("month-to-date"), Ok(DateRange::MonthToDate));
+    }
+
+    #[test]
+    fn last_n_units() {
+        let last = |amount, unit| Ok(DateRange::Last { amount, unit });
+        assert_eq!(parse("last 7 days"), last(7, DateUnit::Day));
+        assert_eq!(parse("last 1 day"), last(1, DateUnit::Day));
+        assert_eq!(parse("last 1 week"), last(1, DateUnit::Week));
+        assert_eq!(parse("last 12 months"), last(12, DateUnit::Month));
+        assert_eq!(parse("last 10000 weeks"), last(10_000, DateUnit::Week));
+    }
+
+    #[test]
+    fn case_and_spaces_are_ignored() {
+        assert_eq!(
+            parse("  This   Month "),
+            Ok(DateRange::This(DatePeriod::Month))
+        );
+        assert_eq!(
+            parse("LAST 3 Weeks"),
+            Ok(DateRange::Last {
+                amount: 3,
+                unit: DateUnit::Week
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        for s in [
+            "",
+            "last month",
+            "last 0 days",
+            "last -1 days",
+            "last 2 years",
+            "last 1.5 days",
+            "last 10001 months",
+            "last 4000000000 days",
+            "next year",
+            "this week",
+            "previous day",
+            "today please",
+        ] {
+            assert!(parse(s).is_err(), "`{s}` is a date range");
+        }
+        assert_eq!(
+            parse("next year"),
+            Err("`next year` is not a date range, e.g. `last 7 days`, `this month` or `previous quarter`".to_owned())
+        );
+    }
+}
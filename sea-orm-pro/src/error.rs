@@ -0,0 +1,199 @@
+use serde::{Serialize, Serializer};
+use std::{ops::Range, path::PathBuf};
+
+use crate::validator::ValidationErrors;
+
+/// Error occurred while loading the admin panel config
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Config directory cannot be walked through
+    #[error("cannot read directory `{}`: {source}", path.display())]
+    ReadDir {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Config file cannot be read
+    #[error("cannot read file `{}`: {source}", path.display())]
+    ReadFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Config file is not a valid TOML / YAML / JSON or doesn't match the config structure
+    #[error("{}{}: {message}", path.display(), location(*line, *column))]
+    Parse {
+        path: PathBuf,
+        message: String,
+        /// Line number of the error, starting from 1
+        line: Option<usize>,
+        /// Column number of the error, starting from 1
+        column: Option<usize>,
+        /// The offending line with a marker under the error
+        snippet: Option<String>,
+    },
+    /// File name can't be used as a table name
+    #[error("invalid config file name `{}`", path.display())]
+    InvalidFileName { path: PathBuf },
+    /// File of an unknown format found in the config directory
+    #[error(
+        "unsupported config file `{}`, expected a `.toml`, `.yaml`, `.yml` or `.json` file",
+        path.display()
+    )]
+    UnsupportedFile { path: PathBuf },
+    /// The same table is configured in more than one file
+    #[error(
+        "table `{name}` is configured in both `{}` and `{}`",
+        first.display(),
+        second.display()
+    )]
+    DuplicateTable {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+    /// The same config is provided in more than one format
+    #[error(
+        "config is provided in both `{}` and `{}`",
+        first.display(),
+        second.display()
+    )]
+    DuplicateFile { first: PathBuf, second: PathBuf },
+    /// A `${VAR}` in a string value cannot be substituted
+    #[error("{}: `{key}`: {message}", path.display())]
+    Interpolation {
+        path: PathBuf,
+        /// Path of the offending value, e.g. `site.theme.title`
+        key: String,
+        message: String,
+    },
+    /// Config doesn't match the registered entities
+    #[error(transparent)]
+    Validation(#[from] ValidationErrors),
+}
+
+impl ConfigError {
+    /// Build a parse error of `content` read from `path`, located by the byte span
+    pub fn parse(path: PathBuf, content: &str, message: &str, span: Option<Range<usize>>) -> Self {
+        let message = message.to_owned();
+        match span {
+            Some(span) => {
+                let (line, column, snippet) = locate(content, span);
+                Self::Parse {
+                    path,
+                    message,
+                    line: Some(line),
+                    column: Some(column),
+                    snippet: Some(snippet),
+                }
+            }
+            None => Self::Parse {
+                path,
+                message,
+                line: None,
+                column: None,
+                snippet: None,
+            },
+        }
+    }
+
+    /// Short name of the error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ReadDir { .. } => "read_dir",
+            Self::ReadFile { .. } => "read_file",
+            Self::Parse { .. } => "parse",
+            Self::InvalidFileName { .. } => "invalid_file_name",
+            Self::UnsupportedFile { .. } => "unsupported_file",
+            Self::DuplicateTable { .. } => "duplicate_table",
+            Self::DuplicateFile { .. } => "duplicate_file",
+            Self::Interpolation { .. } => "interpolation",
+            Self::Validation(_) => "validation",
+        }
+    }
+}
+
+/// Serialized as an error report that can be returned to the developer
+impl Serialize for ConfigError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Report<'a> {
+            kind: &'static str,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            file: Option<&'a PathBuf>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            line: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            column: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            snippet: Option<&'a String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            errors: Option<&'a ValidationErrors>,
+        }
+
+        let mut report = Report {
+            kind: self.kind(),
+            message: self.to_string(),
+            file: None,
+            line: None,
+            column: None,
+            snippet: None,
+            errors: None,
+        };
+        match self {
+            Self::ReadDir { path, .. }
+            | Self::ReadFile { path, .. }
+            | Self::InvalidFileName { path }
+            | Self::UnsupportedFile { path }
+            | Self::Interpolation { path, .. } => report.file = Some(path),
+            Self::Parse {
+                path,
+                line,
+                column,
+                snippet,
+                ..
+            } => {
+                report.file = Some(path);
+                report.line = *line;
+                report.column = *column;
+                report.snippet = snippet.as_ref();
+            }
+            Self::DuplicateTable { second, .. } | Self::DuplicateFile { second, .. } => {
+                report.file = Some(second)
+            }
+            Self::Validation(errors) => report.errors = Some(errors),
+        }
+        report.serialize(serializer)
+    }
+}
+
+fn location(line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(":{line}:{column}"),
+        _ => String::new(),
+    }
+}
+
+/// Line, column and a snippet of the line pointed by the byte span
+fn locate(content: &str, span: Range<usize>) -> (usize, usize, String) {
+    let start = span.start.min(content.len());
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[start..]
+        .find('\n')
+        .map_or(content.len(), |i| start + i);
+    let line = content[..start].matches('\n').count() + 1;
+    let column = content[line_start..start].chars().count() + 1;
+
+    let text = content[line_start..line_end].trim_end_matches('\r');
+    let width = content[start..span.end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+    let snippet = format!(
+        "{line:>4} | {text}\n     | {}{}",
+        " ".repeat(column - 1),
+        "^".repeat(width)
+    );
+    (line, column, snippet)
+}
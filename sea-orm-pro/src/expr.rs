@@ -0,0 +1,633 @@
+use std::fmt;
+
+/// Expression of a virtual column over the columns of the table
+///
+/// Written in a small SQL-like language: column names, numbers, `'strings'`,
+/// `+ - * /`, `||` to concatenate, `coalesce(a, b, ...)` and
+/// `format_date(column, '%Y-%m-%d')`, e.g. `unit_price * (1 - unit_price_discount) * order_qty`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnExpr {
+    /// Column of the table
+    Column(String),
+    /// Integer literal
+    Integer(i64),
+    /// Decimal literal
+    Float(f64),
+    /// String literal
+    String(String),
+    /// Negated number
+    Neg(Box<ColumnExpr>),
+    /// Arithmetic on numbers
+    Binary(Box<ColumnExpr>, BinaryOp, Box<ColumnExpr>),
+    /// Values concatenated as text
+    Concat(Vec<ColumnExpr>),
+    /// First value that isn't null
+    Coalesce(Vec<ColumnExpr>),
+    /// Date or time formatted as text
+    FormatDate(Box<ColumnExpr>, Vec<DatePart>),
+}
+
+/// Arithmetic operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Piece of a date format, `%Y-%m` is `[Year, Text("-"), Month]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatePart {
+    /// `%Y`, e.g. `2025`
+    Year,
+    /// `%m`, e.g. `01`
+    Month,
+    /// `%d`, e.g. `31`
+    Day,
+    /// `%H`, e.g. `23`
+    Hour,
+    /// `%M`, e.g. `59`
+    Minute,
+    /// `%S`, e.g. `59`
+    Second,
+    /// Text in between, `%%` is a literal `%`
+    Text(String),
+}
+
+/// Syntax error of an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    /// Byte offset of the error in the expression
+    pub position: usize,
+    /// Description of the error
+    pub message: String,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+impl ColumnExpr {
+    /// Parse the expression of a virtual column
+    pub fn parse(input: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: input.len(),
+            depth: 0,
+        };
+        let expr = parser.concat()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((position, token)) => Err(ExprError {
+                position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    /// Columns referred to by the expression
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Self::Column(name) => {
+                if !columns.contains(&name.as_str()) {
+                    columns.push(name);
+                }
+            }
+            Self::Integer(_) | Self::Float(_) | Self::String(_) => {}
+            Self::Neg(expr) | Self::FormatDate(expr, _) => expr.collect_columns(columns),
+            Self::Binary(lhs, _, rhs) => {
+                lhs.collect_columns(columns);
+                rhs.collect_columns(columns);
+            }
+            Self::Concat(exprs) | Self::Coalesce(exprs) => {
+                for expr in exprs {
+                    expr.collect_columns(columns);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "`{name}`"),
+            Self::Integer(n) => write!(f, "`{n}`"),
+            Self::Float(n) => write!(f, "`{n}`"),
+            Self::String(s) => write!(f, "'{s}'"),
+            Self::Symbol(s) => write!(f, "`{s}`"),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 8] = ["||", "+", "-", "*", "/", "(", ")", ","];
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars
+                .peek()
+                .filter(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+            {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push((start, Token::Ident(ident)));
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&(_, c)) = chars
+                .peek()
+                .filter(|(_, c)| c.is_ascii_digit() || *c == '.')
+            {
+                number.push(c);
+                chars.next();
+            }
+            let token = match number.parse::<i64>() {
+                Ok(n) => Token::Integer(n),
+                Err(_) => Token::Float(number.parse().map_err(|_| ExprError {
+                    position: start,
+                    message: format!("invalid number `{number}`"),
+                })?),
+            };
+            tokens.push((start, token));
+        } else if c == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    // Quote is escaped by doubling it, as in SQL
+                    Some((_, '\'')) if chars.peek().is_some_and(|(_, c)| *c == '\'') => {
+                        chars.next();
+                        string.push('\'');
+                    }
+                    Some((_, '\'')) => break,
+                    Some((_, c)) => string.push(c),
+                    None => {
+                        return Err(ExprError {
+                            position: start,
+                            message: "unterminated string".to_owned(),
+                        })
+                    }
+                }
+            }
+            tokens.push((start, Token::String(string)));
+        } else {
+            let rest = &input[start..];
+            let Some(symbol) = SYMBOLS.into_iter().find(|symbol| rest.starts_with(symbol)) else {
+                return Err(ExprError {
+                    position: start,
+                    message: format!("unexpected character `{c}`"),
+                });
+            };
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((start, Token::Symbol(symbol)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Deepest nesting of parentheses, function calls and operators, the expression is parsed
+/// and evaluated recursively
+const MAX_DEPTH: usize = 100;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+    /// Nesting of the expression being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens
+            .get(self.pos)
+            .map(|(position, token)| (*position, token))
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), ExprError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or(ExprError {
+            position: self.end,
+            message: "unexpected end of expression".to_owned(),
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    /// Position of the next token
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |(position, _)| position)
+    }
+
+    /// Go one level deeper, the caller goes back up once the nested expression is parsed
+    fn nest(&mut self, position: usize) -> Result<(), ExprError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ExprError {
+                position,
+                message: format!("expression is nested more than {MAX_DEPTH} levels deep"),
+            });
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let matched = matches!(self.peek(), Some((_, Token::Symbol(s))) if *s == symbol);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ExprError> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        let (position, message) = match self.peek() {
+            Some((position, token)) => (position, format!("expected `{symbol}`, found {token}")),
+            None => (self.end, format!("expected `{symbol}`")),
+        };
+        Err(ExprError { position, message })
+    }
+
+    /// `a || b || ...`
+    fn concat(&mut self) -> Result<ColumnExpr, ExprError> {
+        let mut exprs = vec![self.additive()?];
+        while self.eat("||") {
+            exprs.push(self.additive()?);
+        }
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => ColumnExpr::Concat(exprs),
+        })
+    }
+
+    /// `a + b - ...`
+    fn additive(&mut self) -> Result<ColumnExpr, ExprError> {
+        let depth = self.depth;
+        let mut expr = self.term()?;
+        loop {
+            let position = self.position();
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Sub
+            } else {
+                self.depth = depth;
+                return Ok(expr);
+            };
+            // Each operator nests the expression so far one level deeper
+            self.nest(position)?;
+            expr = ColumnExpr::Binary(Box::new(expr), op, Box::new(self.term()?));
+        }
+    }
+
+    /// `a * b / ...`
+    fn term(&mut self) -> Result<ColumnExpr, ExprError> {
+        let depth = self.depth;
+        let mut expr = self.unary()?;
+        loop {
+            let position = self.position();
+            let op = if self.eat("*") {
+                BinaryOp::Mul
+            } else if self.eat("/") {
+                BinaryOp::Div
+            } else {
+                self.depth = depth;
+                return Ok(expr);
+            };
+            self.nest(position)?;
+            expr = ColumnExpr::Binary(Box::new(expr), op, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<ColumnExpr, ExprError> {
+        let position = self.position();
+        if self.eat("-") {
+            self.nest(position)?;
+            let expr = ColumnExpr::Neg(Box::new(self.unary()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<ColumnExpr, ExprError> {
+        let (position, token) = self.next()?;
+        match token {
+            Token::Integer(n) => Ok(ColumnExpr::Integer(n)),
+            Token::Float(n) => Ok(ColumnExpr::Float(n)),
+            Token::String(s) => Ok(ColumnExpr::String(s)),
+            Token::Symbol("(") => {
+                self.nest(position)?;
+                let expr = self.concat()?;
+                self.expect(")")?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.eat("(") => {
+                self.nest(position)?;
+                let expr = self.function(position, &name)?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Token::Ident(name) => Ok(ColumnExpr::Column(name)),
+            token => Err(ExprError {
+                position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    fn function(&mut self, position: usize, name: &str) -> Result<ColumnExpr, ExprError> {
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.concat()?);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(")")?;
+        }
+        let error = |message: String| ExprError { position, message };
+        match name.to_ascii_lowercase().as_str() {
+            "coalesce" if args.is_empty() => {
+                Err(error("`coalesce` takes at least 1 argument".to_owned()))
+            }
+            "coalesce" => Ok(ColumnExpr::Coalesce(args)),
+            "format_date" => match <[ColumnExpr; 2]>::try_from(args) {
+                Ok([expr, ColumnExpr::String(format)]) => Ok(ColumnExpr::FormatDate(
+                    Box::new(expr),
+                    date_format(&format).map_err(error)?,
+                )),
+                _ => Err(error(
+                    "`format_date` takes a date and a format, e.g. `format_date(order_date, '%Y-%m-%d')`"
+                        .to_owned(),
+                )),
+            },
+            _ => Err(error(format!("unknown function `{name}`"))),
+        }
+    }
+}
+
+fn date_format(format: &str) -> Result<Vec<DatePart>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+        let part = match chars.next() {
+            Some('%') => {
+                text.push('%');
+                continue;
+            }
+            Some('Y') => DatePart::Year,
+            Some('m') => DatePart::Month,
+            Some('d') => DatePart::Day,
+            Some('H') => DatePart::Hour,
+            Some('M') => DatePart::Minute,
+            Some('S') => DatePart::Second,
+            Some(c) => return Err(format!("unsupported date format `%{c}`")),
+            None => return Err("date format ends with `%`".to_owned()),
+        };
+        if !text.is_empty() {
+            parts.push(DatePart::Text(std::mem::take(&mut text)));
+        }
+        parts.push(part);
+    }
+    if !text.is_empty() {
+        parts.push(DatePart::Text(text));
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(name: &str) -> Box<ColumnExpr> {
+        Box::new(ColumnExpr::Column(name.to_owned()))
+    }
+
+    fn binary(lhs: Box<ColumnExpr>, op: BinaryOp, rhs: Box<ColumnExpr>) -> Box<ColumnExpr> {
+        Box::new(ColumnExpr::Binary(lhs, op, rhs))
+    }
+
+    fn error(input: &str) -> ExprError {
+        ColumnExpr::parse(input).unwrap_err()
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(
+            ColumnExpr::parse("a + b * c").unwrap(),
+            *binary(
+                col("a"),
+                BinaryOp::Add,
+                binary(col("b"), BinaryOp::Mul, col("c"))
+            )
+        );
+        assert_eq!(
+            ColumnExpr::parse("a / b - c").unwrap(),
+            *binary(
+                binary(col("a"), BinaryOp::Div, col("b")),
+                BinaryOp::Sub,
+                col("c")
+            )
+        );
+    }
+
+    #[test]
+    fn operators_of_a_level_are_left_associative() {
+        assert_eq!(
+            ColumnExpr::parse("a - b - c").unwrap(),
+            *binary(
+                binary(col("a"), BinaryOp::Sub, col("b")),
+                BinaryOp::Sub,
+                col("c")
+            )
+        );
+        assert_eq!(
+            ColumnExpr::parse("a / b * c").unwrap(),
+            *binary(
+                binary(col("a"), BinaryOp::Div, col("b")),
+                BinaryOp::Mul,
+                col("c")
+            )
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            ColumnExpr::parse("unit_price * (1 - discount)").unwrap(),
+            *binary(
+                col("unit_price"),
+                BinaryOp::Mul,
+                binary(
+                    Box::new(ColumnExpr::Integer(1)),
+                    BinaryOp::Sub,
+                    col("discount")
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn negation_binds_tighter_than_multiplication() {
+        assert_eq!(
+            ColumnExpr::parse("-a * b").unwrap(),
+            *binary(Box::new(ColumnExpr::Neg(col("a"))), BinaryOp::Mul, col("b"))
+        );
+    }
+
+    #[test]
+    fn concatenation_binds_loosest() {
+        assert_eq!(
+            ColumnExpr::parse("'SO' || a + 1 || b").unwrap(),
+            ColumnExpr::Concat(vec![
+                ColumnExpr::String("SO".to_owned()),
+                *binary(col("a"), BinaryOp::Add, Box::new(ColumnExpr::Integer(1))),
+                ColumnExpr::Column("b".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(
+            ColumnExpr::parse("COALESCE(a, 'n/a')").unwrap(),
+            ColumnExpr::Coalesce(vec![
+                ColumnExpr::Column("a".to_owned()),
+                ColumnExpr::String("n/a".to_owned()),
+            ])
+        );
+        assert_eq!(
+            ColumnExpr::parse("format_date(order_date, '%Y/%m %%')").unwrap(),
+            ColumnExpr::FormatDate(
+                col("order_date"),
+                vec![
+                    DatePart::Year,
+                    DatePart::Text("/".to_owned()),
+                    DatePart::Month,
+                    DatePart::Text(" %".to_owned()),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn columns_are_listed_once() {
+        let expr = ColumnExpr::parse("a * b + coalesce(a, c)").unwrap();
+        assert_eq!(expr.columns(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn errors_point_at_the_position() {
+        assert_eq!(
+            error("a +"),
+            ExprError {
+                position: 3,
+                message: "unexpected end of expression".to_owned(),
+            }
+        );
+        assert_eq!(
+            error("(a + b"),
+            ExprError {
+                position: 6,
+                message: "expected `)`".to_owned(),
+            }
+        );
+        assert_eq!(error("a b").position, 2);
+        assert_eq!(error("a * * b").position, 4);
+    }
+
+    #[test]
+    fn errors_of_tokens() {
+        assert_eq!(
+            error("'abc"),
+            ExprError {
+                position: 0,
+                message: "unterminated string".to_owned(),
+            }
+        );
+        assert_eq!(error("a ; b").message, "unexpected character `;`");
+        assert_eq!(error("1.2.3").message, "invalid number `1.2.3`");
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(ColumnExpr::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            error(&nested(MAX_DEPTH + 1)),
+            ExprError {
+                position: MAX_DEPTH,
+                message: "expression is nested more than 100 levels deep".to_owned(),
+            }
+        );
+        let negated = format!("{}a", "-".repeat(100_000));
+        assert_eq!(error(&negated).position, MAX_DEPTH);
+        let calls = format!("{}a{}", "coalesce(".repeat(1_000), ")".repeat(1_000));
+        assert_eq!(error(&calls).position, MAX_DEPTH * 9);
+        // Operators nest the expression on their left
+        let sum = |terms: usize| vec!["a"; terms].join(" + ");
+        assert!(ColumnExpr::parse(&sum(MAX_DEPTH + 1)).is_ok());
+        assert_eq!(error(&sum(MAX_DEPTH + 2)).position, MAX_DEPTH * 4 + 2);
+        assert!(ColumnExpr::parse(&format!("({}) * b", sum(MAX_DEPTH))).is_ok());
+        assert!(ColumnExpr::parse(&format!("({}) * b", sum(MAX_DEPTH + 1))).is_err());
+    }
+
+    #[test]
+    fn errors_of_functions() {
+        assert_eq!(error("upper(a)").message, "unknown function `upper`");
+        assert_eq!(
+            error("coalesce()").message,
+            "`coalesce` takes at least 1 argument"
+        );
+        assert!(error("format_date(a)")
+            .message
+            .starts_with("`format_date` takes"));
+        assert_eq!(
+            error("format_date(a, '%Q')").message,
+            "unsupported date format `%Q`"
+        );
+        assert_eq!(
+            error("format_date(a, '%Y%')").message,
+            "date format ends with `%`"
+        );
+    }
+}
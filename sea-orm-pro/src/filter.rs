@@ -0,0 +1,55 @@
+use serde_json::Value;
+
+use crate::{
+    config::{FilterConditionCfg, FilterOp},
+    date_range::DateRange,
+    meta::{ColumnKind, EntityMeta},
+};
+
+impl FilterConditionCfg {
+    /// Check the column exists and the value fits the operator
+    pub fn check(&self, entity: &EntityMeta) -> Result<(), String> {
+        let Some(column) = entity.column(&self.field) else {
+            return Err(format!(
+                "column `{}` does not exist on table `{}`",
+                self.field, entity.table_name
+            ));
+        };
+        let is_scalar = |value: &Value| !matches!(value, Value::Array(_) | Value::Object(_));
+        let op = serde_json::to_value(self.op).unwrap_or_default();
+        let op = op.as_str().unwrap_or_default();
+        let fits = match self.op {
+            FilterOp::IsNull | FilterOp::IsNotNull => self.value.is_null(),
+            FilterOp::IsIn | FilterOp::IsNotIn => match &self.value {
+                Value::Array(values) => values
+                    .iter()
+                    .all(|value| !value.is_null() && is_scalar(value)),
+                _ => false,
+            },
+            FilterOp::Between => match &self.value {
+                Value::Array(values) => values.len() == 2 && values.iter().all(is_scalar),
+                _ => false,
+            },
+            FilterOp::Last => {
+                if !matches!(column.kind, ColumnKind::Date | ColumnKind::DateTime) {
+                    return Err(format!(
+                        "`{op}` needs a date column, `{}` is not",
+                        self.field
+                    ));
+                }
+                return match &self.value {
+                    Value::String(value) => value.parse::<DateRange>().map(|_| ()),
+                    value => Err(format!(
+                        "`{value}` is not a date range, e.g. `last 30 days`"
+                    )),
+                };
+            }
+            _ => !self.value.is_null() && is_scalar(&self.value),
+        };
+        if fits {
+            Ok(())
+        } else {
+            Err(format!("`{}` is not a value of `{op}`", self.value))
+        }
+    }
+}
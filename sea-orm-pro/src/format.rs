@@ -0,0 +1,78 @@
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+use crate::error::ConfigError;
+
+/// File format of config files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Supported file extensions, in the order of precedence
+    pub const EXTENSIONS: [&'static str; 4] = ["toml", "yaml", "yml", "json"];
+
+    /// Detect the format by file extension
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Detect the format by the extension of the file path
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Parse the content of the config file at `path`
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, content: &str) -> Result<T, ConfigError> {
+        match self {
+            Self::Toml => toml::from_str(content)
+                .map_err(|err| ConfigError::parse(path.into(), content, err.message(), err.span())),
+            Self::Yaml => serde_yaml::from_str(content).map_err(|err| {
+                let span = err.location().map(|loc| loc.index()..loc.index());
+                // Location is rendered separately, strip it from the message
+                let message = err.to_string();
+                let message = match message.rsplit_once(" at line ") {
+                    Some((message, _)) if span.is_some() => message.to_owned(),
+                    _ => message,
+                };
+                ConfigError::parse(path.into(), content, &message, span)
+            }),
+            Self::Json => serde_json::from_str(content).map_err(|err| {
+                let offset = offset_of(content, err.line(), err.column());
+                let message = err.to_string();
+                let message = match message.rsplit_once(" at line ") {
+                    Some((message, _)) => message.to_owned(),
+                    None => message,
+                };
+                ConfigError::parse(path.into(), content, &message, offset.map(|i| i..i))
+            }),
+        }
+    }
+}
+
+/// Byte offset of the 1-based line and column
+fn offset_of(content: &str, line: usize, column: usize) -> Option<usize> {
+    if line == 0 {
+        return None;
+    }
+    let line_start = if line == 1 {
+        0
+    } else {
+        content.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    let mut offset = (line_start + column.saturating_sub(1)).min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    Some(offset)
+}
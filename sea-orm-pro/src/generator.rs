@@ -0,0 +1,280 @@
+use serde::Serialize;
+
+use crate::{
+    config::{
+        ChildTableCfg, ColumnCfg, CompositeTableCfg, CreateCfg, DeleteCfg, EditorCfg, FieldCfg,
+        FilterCfg, InputType, Order, ParentTableCfg, RawTableCfg, TableCfg, UpdateCfg,
+        ViewOrderByCfg,
+    },
+    meta::{ColumnKind, ColumnMeta, EntityMeta, RelationKind},
+};
+
+/// Columns filled in by the database or the application, hidden on the create and update form
+const AUTO_COLUMNS: [&str; 6] = [
+    "created_at",
+    "created_date",
+    "updated_at",
+    "updated_date",
+    "modified_date",
+    "rowguid",
+];
+
+/// Columns used as the title of a record, in the order of preference
+const TITLE_COLUMNS: [&str; 5] = ["name", "title", "full_name", "display_name", "email"];
+
+/// Keys rendered as `[section]` in the TOML file, other tables are rendered inline
+const SECTIONS: [&str; 9] = [
+    "parent", "children", "table", "filter", "view", "editor", "create", "update", "delete",
+];
+
+impl RawTableCfg {
+    /// Default config of the table derived from the entity metadata
+    pub fn generate(entity: &EntityMeta) -> Self {
+        let primary_key: Vec<_> = entity.primary_key().collect();
+        let single_key = primary_key.len() == 1;
+        let auto_increment = single_key && primary_key[0].kind == ColumnKind::Integer;
+        let is_auto = |column: &ColumnMeta| {
+            AUTO_COLUMNS.contains(&column.name.as_str()) || (auto_increment && column.primary_key)
+        };
+        let is_hidden = |column: &ColumnMeta| {
+            matches!(column.kind, ColumnKind::Binary | ColumnKind::Json) || column.is_secret()
+        };
+        let names = |filter: &dyn Fn(&ColumnMeta) -> bool| -> Vec<String> {
+            entity
+                .columns
+                .iter()
+                .filter(|column| filter(column))
+                .map(|column| column.name.clone())
+                .collect()
+        };
+
+        let columns = entity
+            .columns
+            .iter()
+            .filter(|column| !column.is_secret())
+            .map(|column| {
+                let input_type = input_type(column);
+                ColumnCfg {
+                    title: (single_key && column.primary_key).then(|| "ID".to_owned()),
+                    field: column.name.clone(),
+                    width: if single_key && column.primary_key {
+                        Some(80)
+                    } else if matches!(input_type, Some(InputType::Image { .. })) {
+                        Some(120)
+                    } else {
+                        None
+                    },
+                    input_type,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let fields = entity
+            .columns
+            .iter()
+            .filter(|column| input_type(column) == Some(InputType::Textarea))
+            .map(|column| FieldCfg {
+                field: column.name.clone(),
+                span: Some(12),
+                input_type: Some(InputType::Textarea),
+                rows: Some(4),
+                ..Default::default()
+            })
+            .collect();
+        let title_field = title_field(entity);
+
+        RawTableCfg {
+            table: TableCfg {
+                all_columns: false,
+                columns,
+                hidden_columns: names(&is_hidden),
+                order_by: primary_key.first().map(|pk| ViewOrderByCfg {
+                    field: pk.name.clone(),
+                    order: Order::Desc,
+                }),
+                title: Some(title_case(&entity.table_name)),
+                ..Default::default()
+            },
+            filter: FilterCfg {
+                hidden_columns: names(&|column| {
+                    is_hidden(column) || matches!(input_type(column), Some(InputType::Image { .. }))
+                }),
+                ..Default::default()
+            },
+            view: Default::default(),
+            editor: EditorCfg {
+                enable: title_field.is_some(),
+                title_field,
+                fields,
+            },
+            create: CreateCfg {
+                enable: true,
+                hidden_columns: names(&|column| is_auto(column) || column.is_secret()),
+            },
+            update: UpdateCfg {
+                enable: true,
+                hidden_columns: names(&|column| {
+                    !column.primary_key && (is_auto(column) || column.is_secret())
+                }),
+                readonly_columns: names(&|column| column.primary_key),
+            },
+            delete: DeleteCfg { enable: true },
+            permissions: Default::default(),
+        }
+    }
+
+    /// Render the config as TOML, in the layout of the hand-written config files
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        render(self)
+    }
+}
+
+impl CompositeTableCfg {
+    /// Default config of the parent table with a child table for each relation of the parent,
+    /// relations to tables missing in `entities` are skipped
+    pub fn generate(parent: &EntityMeta, entities: &[EntityMeta]) -> Self {
+        let children = parent
+            .relations
+            .iter()
+            .filter_map(|relation| {
+                let entity = entities
+                    .iter()
+                    .find(|entity| entity.table_name == relation.to_table)?;
+                let mut child_config = RawTableCfg::generate(entity);
+                child_config.table.title = Some(title_case(&relation.name));
+                if matches!(relation.kind, RelationKind::HasOne | RelationKind::HasMany) {
+                    // Foreign key of the child rows always refers to the parent shown above
+                    child_config
+                        .table
+                        .columns
+                        .retain(|column| !relation.to_columns.contains(&column.field));
+                }
+                Some(ChildTableCfg {
+                    relation: relation.name.clone(),
+                    child_config,
+                })
+            })
+            .collect();
+
+        CompositeTableCfg {
+            parent: ParentTableCfg {
+                name: parent.table_name.clone(),
+                parent_config: RawTableCfg::generate(parent),
+            },
+            children,
+        }
+    }
+
+    /// Render the config as TOML, in the layout of the hand-written config files
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        render(self)
+    }
+}
+
+/// Input type of the column, `None` if the default input fits
+fn input_type(column: &ColumnMeta) -> Option<InputType> {
+    let name = column.name.as_str();
+    let is_image = ["photo", "image", "avatar", "thumbnail"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix));
+    match column.kind {
+        ColumnKind::Binary | ColumnKind::String | ColumnKind::Text if is_image => {
+            Some(InputType::Image { url_prefix: None })
+        }
+        ColumnKind::Binary => Some(InputType::Image { url_prefix: None }),
+        ColumnKind::Text => Some(InputType::Textarea),
+        _ => None,
+    }
+}
+
+/// Guess the column to display as the title of a record, from the name-like string columns
+fn title_field(entity: &EntityMeta) -> Option<String> {
+    let candidates: Vec<_> = entity
+        .columns
+        .iter()
+        .filter(|column| !column.primary_key)
+        .filter(|column| matches!(column.kind, ColumnKind::String | ColumnKind::Text))
+        .collect();
+    TITLE_COLUMNS
+        .iter()
+        .find_map(|name| candidates.iter().find(|column| column.name == *name))
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|column| column.name.ends_with("_name") || column.name.ends_with("_title"))
+        })
+        .map(|column| column.name.clone())
+}
+
+/// `sales_order_header` => `Sales Order Header`
+pub(crate) fn title_case(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Render the config as TOML, sections as `[section]` with arrays written one item per line
+fn render<T: Serialize>(config: &T) -> Result<String, toml::ser::Error> {
+    let table = toml::Table::try_from(config)?;
+    let mut out = String::new();
+    render_table(&mut out, None, &table);
+    Ok(out.trim_start().to_owned())
+}
+
+fn render_table(out: &mut String, header: Option<(&str, bool)>, table: &toml::Table) {
+    let is_section = |key: &str, value: &toml::Value| {
+        SECTIONS.contains(&key)
+            && match value {
+                toml::Value::Table(_) => true,
+                toml::Value::Array(items) => {
+                    !items.is_empty() && items.iter().all(toml::Value::is_table)
+                }
+                _ => false,
+            }
+    };
+    let (sections, values): (Vec<_>, Vec<_>) = table
+        .iter()
+        .partition(|(key, value)| is_section(key, value));
+
+    match header {
+        Some((header, true)) => out.push_str(&format!("\n[[{header}]]\n")),
+        Some((header, false)) if !values.is_empty() => out.push_str(&format!("\n[{header}]\n")),
+        _ => {}
+    }
+    for (key, value) in values {
+        match value {
+            toml::Value::Array(items) if !items.is_empty() => {
+                out.push_str(&format!("{key} = [\n"));
+                for item in items {
+                    out.push_str(&format!("    {item},\n"));
+                }
+                out.push_str("]\n");
+            }
+            value => out.push_str(&format!("{key} = {value}\n")),
+        }
+    }
+    for (key, value) in sections {
+        let name = match header {
+            Some((header, _)) => format!("{header}.{key}"),
+            None => key.clone(),
+        };
+        match value {
+            toml::Value::Table(table) => render_table(out, Some((&name, false)), table),
+            toml::Value::Array(items) => {
+                for item in items.iter().filter_map(toml::Value::as_table) {
+                    out.push('\n');
+                    render_table(out, Some((&name, true)), item);
+                }
+            }
+            _ => unreachable!("only tables and arrays of tables are sections"),
+        }
+    }
+}
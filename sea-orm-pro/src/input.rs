@@ -0,0 +1,408 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use validator::{ValidateEmail, ValidateUrl};
+
+use crate::{
+    config::{
+        ColumnCfg, FieldCfg, InputType, JsonCfg, OptionLabel, RawTableCfg, RulesCfg, SelectOption,
+        SelectOptions, VirtualColumnCfg,
+    },
+    generator::title_case,
+    meta::{ColumnKind, ColumnMeta, EntityMeta},
+};
+
+/// Invalid value of a field in the data written to a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Name of the SQL column
+    pub field: String,
+    /// Description of the error
+    pub message: String,
+}
+
+/// Kind of write checked against the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    /// New rows, every required field must be given
+    Create,
+    /// Existing rows, only the given fields are checked
+    Update,
+}
+
+impl JsonCfg {
+    /// Turn the options written as a map or an enum reference into a list of options,
+    /// keys of the map are converted to the type of the column, e.g. `"1"` to `1`
+    pub fn resolve_options(&mut self, entities: &[EntityMeta]) {
+        let find = |table: &str| entities.iter().find(|entity| entity.table_name == table);
+        for (name, raw_table) in self.raw_tables.iter_mut() {
+            if let Some(entity) = find(name) {
+                resolve_table(entity, entities, raw_table);
+            }
+        }
+        for composite_table in self.composite_tables.values_mut() {
+            let parent = &mut composite_table.parent;
+            let Some(entity) = find(&parent.name) else {
+                continue;
+            };
+            resolve_table(entity, entities, &mut parent.parent_config);
+            for child in composite_table.children.iter_mut() {
+                let child_entity = entity
+                    .relation(&child.relation)
+                    .and_then(|relation| find(&relation.to_table));
+                if let Some(child_entity) = child_entity {
+                    resolve_table(child_entity, entities, &mut child.child_config);
+                }
+            }
+        }
+    }
+
+    /// Check a row written to the table against the options and the rules of the columns
+    /// and fields, `row` is keyed by column name and may only contain the columns being written.
+    /// The `unique` rule needs the database, see [`JsonCfg::unique_fields`].
+    pub fn check_input(
+        &self,
+        entities: &[EntityMeta],
+        table: &str,
+        row: &Map<String, Value>,
+        mode: WriteMode,
+    ) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        for (field, rules) in self.field_rules(entities, table) {
+            let value = row.get(field);
+            if mode == WriteMode::Update && value.is_none() {
+                continue;
+            }
+            let message = check_rules(rules, value.unwrap_or(&Value::Null));
+            if let Some(message) = message {
+                errors.push(FieldError {
+                    field: field.to_owned(),
+                    message: rules.message.clone().unwrap_or(message),
+                });
+            }
+        }
+        for (field, value) in row.iter() {
+            if value.is_null() || errors.iter().any(|error| error.field == *field) {
+                continue;
+            }
+            for (options, multiple) in self.field_options(entities, table, field) {
+                let values = match (multiple, value) {
+                    (true, Value::Array(values)) => values.iter().collect(),
+                    _ => vec![value],
+                };
+                let invalid = values
+                    .into_iter()
+                    .find(|value| !options.iter().any(|option| value_eq(&option.value, value)));
+                if let Some(invalid) = invalid {
+                    errors.push(FieldError {
+                        field: field.clone(),
+                        message: format!(
+                            "{invalid} is not one of {}",
+                            options
+                                .iter()
+                                .map(|option| option.value.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    });
+                    break;
+                }
+            }
+        }
+        errors
+    }
+
+    /// Config of the table, including the parent and child tables of composite tables
+    pub fn table_configs<'a>(
+        &'a self,
+        entities: &'a [EntityMeta],
+        table: &'a str,
+    ) -> impl Iterator<Item = &'a RawTableCfg> {
+        let raw_tables = self.raw_tables.get(table);
+        let composite_tables = self.composite_tables.values().flat_map(move |composite| {
+            let parent = &composite.parent;
+            let entity = entities
+                .iter()
+                .find(|entity| entity.table_name == parent.name);
+            // Child tables are named by the relation of the parent
+            let children = composite
+                .children
+                .iter()
+                .filter(move |child| {
+                    entity
+                        .and_then(|entity| entity.relation(&child.relation))
+                        .is_some_and(|relation| relation.to_table == table)
+                })
+                .map(|child| &child.child_config);
+            (parent.name == table)
+                .then_some(&parent.parent_config)
+                .into_iter()
+                .chain(children)
+        });
+        raw_tables.into_iter().chain(composite_tables)
+    }
+
+    /// Virtual columns of the table in all config of the table, by name
+    pub fn virtual_columns(&self, entities: &[EntityMeta], table: &str) -> Vec<VirtualColumnCfg> {
+        let mut columns: Vec<VirtualColumnCfg> = Vec::new();
+        for cfg in self.table_configs(entities, table) {
+            for column in cfg.table.virtual_columns.iter() {
+                if columns.iter().all(|c| c.field != column.field) {
+                    columns.push(column.clone());
+                }
+            }
+        }
+        columns
+    }
+
+    /// Fields of the table with the `unique` rule
+    pub fn unique_fields<'a>(
+        &'a self,
+        entities: &'a [EntityMeta],
+        table: &'a str,
+    ) -> impl Iterator<Item = &'a str> {
+        self.field_rules(entities, table)
+            .filter(|(_, rules)| rules.unique == Some(true))
+            .map(|(field, _)| field)
+    }
+
+    /// Rules of the fields in all config of the table
+    fn field_rules<'a>(
+        &'a self,
+        entities: &'a [EntityMeta],
+        table: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a RulesCfg)> {
+        self.table_configs(entities, table).flat_map(|cfg| {
+            cfg.editor
+                .fields
+                .iter()
+                .filter_map(|field| Some((field.field.as_str(), field.rules.as_ref()?)))
+        })
+    }
+
+    /// Resolved options of the column in all config of the table, and whether
+    /// many options can be picked
+    fn field_options<'a>(
+        &'a self,
+        entities: &'a [EntityMeta],
+        table: &'a str,
+        field: &'a str,
+    ) -> impl Iterator<Item = (&'a [SelectOption], bool)> {
+        self.table_configs(entities, table).flat_map(move |cfg| {
+            let columns = cfg
+                .table
+                .columns
+                .iter()
+                .filter(move |column| column.relation.is_none() && column.field == field)
+                .flat_map(|column| options_of(&column.options, &column.input_type));
+            let fields = cfg
+                .editor
+                .fields
+                .iter()
+                .filter(move |f| f.field == field)
+                .flat_map(|f| options_of(&f.options, &f.input_type));
+            columns.chain(fields)
+        })
+    }
+}
+
+/// Check the value against the rules, the message of the first broken rule is returned
+fn check_rules(rules: &RulesCfg, value: &Value) -> Option<String> {
+    let is_empty = match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        _ => false,
+    };
+    if is_empty {
+        return (rules.required == Some(true)).then(|| "is required".to_owned());
+    }
+
+    let text = match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let length = text.as_ref().map(|text| text.chars().count());
+    if let (Some(min_length), Some(length)) = (rules.min_length, length) {
+        if length < min_length {
+            return Some(format!("must be at least {min_length} characters"));
+        }
+    }
+    if let (Some(max_length), Some(length)) = (rules.max_length, length) {
+        if length > max_length {
+            return Some(format!("must be at most {max_length} characters"));
+        }
+    }
+
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    };
+    if rules.min.is_some() || rules.max.is_some() {
+        let Some(number) = number else {
+            return Some("must be a number".to_owned());
+        };
+        if let Some(min) = rules.min.filter(|min| number < *min) {
+            return Some(format!("must be at least {min}"));
+        }
+        if let Some(max) = rules.max.filter(|max| number > *max) {
+            return Some(format!("must be at most {max}"));
+        }
+    }
+
+    let text = text?;
+    if let Some(pattern) = &rules.pattern {
+        // Broken patterns are reported when the config is validated
+        if Regex::new(pattern).is_ok_and(|regex| !regex.is_match(&text)) {
+            return Some(format!("must match `{pattern}`"));
+        }
+    }
+    if rules.email == Some(true) && !text.validate_email() {
+        return Some("must be an email address".to_owned());
+    }
+    if rules.url == Some(true) && !text.validate_url() {
+        return Some("must be a URL".to_owned());
+    }
+    None
+}
+
+/// Resolved options of a column or field
+fn options_of<'a>(
+    options: &'a Option<SelectOptions>,
+    input_type: &'a Option<InputType>,
+) -> Vec<(&'a [SelectOption], bool)> {
+    let mut res = Vec::new();
+    if let Some(SelectOptions::List(options)) = options {
+        res.push((options.as_slice(), false));
+    }
+    match input_type {
+        Some(InputType::Select {
+            options: SelectOptions::List(options),
+        }) if !options.is_empty() => res.push((options.as_slice(), false)),
+        Some(InputType::MultiSelect {
+            options: SelectOptions::List(options),
+        }) if !options.is_empty() => res.push((options.as_slice(), true)),
+        _ => {}
+    }
+    res
+}
+
+fn resolve_table(entity: &EntityMeta, entities: &[EntityMeta], cfg: &mut RawTableCfg) {
+    for column in cfg.table.columns.iter_mut() {
+        let ColumnCfg {
+            field,
+            relation,
+            options,
+            input_type,
+            ..
+        } = column;
+        // Column of the related table
+        let entity = match relation {
+            Some(relation) => entity
+                .relation(relation)
+                .and_then(|relation| entities.iter().find(|e| e.table_name == relation.to_table)),
+            None => Some(entity),
+        };
+        if let Some(column) = entity.and_then(|entity| entity.column(field)) {
+            resolve_column(column, options, input_type);
+        }
+    }
+    for field in cfg.editor.fields.iter_mut() {
+        let FieldCfg {
+            field,
+            options,
+            input_type,
+            ..
+        } = field;
+        if let Some(column) = entity.column(field) {
+            resolve_column(column, options, input_type);
+        }
+    }
+}
+
+fn resolve_column(
+    column: &ColumnMeta,
+    options: &mut Option<SelectOptions>,
+    input_type: &mut Option<InputType>,
+) {
+    if let Some(options) = options {
+        resolve(column, options);
+    }
+    if let Some(InputType::Select { options } | InputType::MultiSelect { options }) = input_type {
+        resolve(column, options);
+    }
+}
+
+fn resolve(column: &ColumnMeta, options: &mut SelectOptions) {
+    let list = match options {
+        SelectOptions::List(_) => return,
+        SelectOptions::Enum { .. } => column
+            .enum_values
+            .iter()
+            .map(|value| SelectOption {
+                value: Value::String(value.clone()),
+                label: title_case(value),
+                color: None,
+                badge: None,
+            })
+            .collect(),
+        SelectOptions::Map(map) => map
+            .iter()
+            .map(|(value, label)| {
+                let value = parse_value(column, value).unwrap_or_else(|| value.clone().into());
+                match label {
+                    OptionLabel::Label(label) => SelectOption {
+                        value,
+                        label: label.clone(),
+                        color: None,
+                        badge: None,
+                    },
+                    OptionLabel::Styled {
+                        label,
+                        color,
+                        badge,
+                    } => SelectOption {
+                        value,
+                        label: label.clone(),
+                        color: color.clone(),
+                        badge: *badge,
+                    },
+                }
+            })
+            .collect(),
+    };
+    *options = SelectOptions::List(list);
+}
+
+/// Parse the key of the option map as a value of the column, `None` if it doesn't fit
+pub(crate) fn parse_value(column: &ColumnMeta, value: &str) -> Option<Value> {
+    match column.kind {
+        ColumnKind::Integer => value.parse::<i64>().ok().map(Into::into),
+        ColumnKind::Float | ColumnKind::Decimal => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        ColumnKind::Boolean => value.parse::<bool>().ok().map(Into::into),
+        _ => Some(value.into()),
+    }
+}
+
+/// Compare values regardless of their representation, e.g. decimals are sent as strings
+fn value_eq(a: &Value, b: &Value) -> bool {
+    let as_f64 = |value: &Value| match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    let as_str = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    match (as_f64(a), as_f64(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => as_str(a) == as_str(b),
+    }
+}
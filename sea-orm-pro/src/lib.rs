@@ -1,5 +1,29 @@
+pub mod chart;
 pub mod config;
+pub mod date_range;
+pub mod error;
+pub mod expr;
+pub mod filter;
+pub mod format;
+pub mod generator;
+pub mod input;
+pub mod meta;
 pub mod parser;
+pub mod permission;
+pub mod schema;
+pub mod source;
+pub mod validator;
 
+pub use chart::*;
 pub use config::*;
+pub use date_range::*;
+pub use error::*;
+pub use expr::*;
+pub use format::*;
+pub use input::*;
+pub use meta::*;
 pub use parser::*;
+pub use permission::*;
+pub use schema::*;
+pub use source::*;
+pub use validator::*;
@@ -0,0 +1,111 @@
+/// Metadata of a SeaORM entity, used to cross-check the admin panel config
+#[derive(Debug, Clone, Default)]
+pub struct EntityMeta {
+    /// Name of the SQL table
+    pub table_name: String,
+    /// Columns of the table
+    pub columns: Vec<ColumnMeta>,
+    /// Relations of the entity, as listed in the `RelatedEntity` enum
+    pub relations: Vec<RelationMeta>,
+}
+
+/// Metadata of a column
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMeta {
+    /// Name of the SQL column
+    pub name: String,
+    /// Data type of the column
+    pub kind: ColumnKind,
+    /// Is the column nullable
+    pub nullable: bool,
+    /// Is the column part of the primary key
+    pub primary_key: bool,
+    /// Has unique constraint
+    pub unique: bool,
+    /// Name of the enum type, if the column is an enum
+    pub enum_name: Option<String>,
+    /// Variants of the enum type
+    pub enum_values: Vec<String>,
+}
+
+/// Data type of a column, simplified from the SQL column type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Short string such as `char` and `varchar`
+    String,
+    /// Long text
+    Text,
+    Integer,
+    Float,
+    Decimal,
+    Boolean,
+    Date,
+    Time,
+    DateTime,
+    Json,
+    Uuid,
+    /// Binary data such as `blob` and `bytea`
+    Binary,
+    Enum,
+    #[default]
+    Other,
+}
+
+/// Metadata of a relation
+#[derive(Debug, Clone, Default)]
+pub struct RelationMeta {
+    /// Name of the SeaORM relation in snake case, e.g. `sales_order_detail`
+    pub name: String,
+    /// Name of the SQL table on the other side of the relation
+    pub to_table: String,
+    /// Kind of the relation
+    pub kind: RelationKind,
+    /// Columns of this table joined by the relation, empty for a via relation
+    pub from_columns: Vec<String>,
+    /// Columns of the table on the other side, empty for a via relation
+    pub to_columns: Vec<String>,
+    /// Table in between of a via relation, e.g. `sales_order_detail` for
+    /// `sales_order_header -> product`
+    pub via: Option<String>,
+}
+
+/// Kind of a relation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelationKind {
+    /// `has_one`, the other table holds the foreign key
+    HasOne,
+    /// `has_many`, the other table holds the foreign key
+    HasMany,
+    /// `belongs_to`, this table holds the foreign key
+    BelongsTo,
+    /// Many-to-many relation through a junction table
+    #[default]
+    Via,
+}
+
+impl EntityMeta {
+    /// Find column by name
+    pub fn column(&self, name: &str) -> Option<&ColumnMeta> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Columns of the primary key
+    pub fn primary_key(&self) -> impl Iterator<Item = &ColumnMeta> {
+        self.columns.iter().filter(|column| column.primary_key)
+    }
+
+    /// Find relation by name
+    pub fn relation(&self, name: &str) -> Option<&RelationMeta> {
+        self.relations.iter().find(|relation| relation.name == name)
+    }
+}
+
+impl ColumnMeta {
+    /// Credentials such as password hashes and tokens, never shown in the admin panel,
+    /// e.g. `password`, `password_hash` or `reset_token`
+    pub fn is_secret(&self) -> bool {
+        ["password", "secret", "token", "api_key"]
+            .iter()
+            .any(|word| self.name.contains(word))
+    }
+}
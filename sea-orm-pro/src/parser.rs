@@ -1,55 +1,413 @@
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
 use crate::{
-    config::{CompositeTableCfg, JsonCfg, RawTableCfg},
+    config::{CompositeTableCfg, JsonCfg, RawTableCfg, SiteCfg, TableSource},
+    error::ConfigError,
+    format::ConfigFormat,
+    source::ConfigDir,
     DashboardCfg,
 };
 
-pub struct ConfigParser {}
+/// Loader of the admin panel config
+///
+/// With an environment set, files in `overlays/<environment>/` mirroring the layout of the
+/// root folder are deep merged over the base files: tables are merged key by key,
+/// while arrays and plain values are replaced. `${VAR}` in string values are substituted
+/// with the environment variable of the same name.
+pub struct ConfigParser {
+    environment: Option<String>,
+}
 
 impl ConfigParser {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self { environment: None }
     }
 
-    pub fn load_config<T: Into<String>>(self, root_folder: T) -> anyhow::Result<JsonCfg> {
-        let root_folder = root_folder.into();
-        // Load site config
-        let content = std::fs::read_to_string(format!("{root_folder}/config.toml"))
-            .unwrap_or(DEFAULT_SITE_CONFIG.into());
-        let mut json_config: JsonCfg = toml::from_str(&content)?;
+    /// Merge the overlay of the environment, e.g. `development` or `production`
+    pub fn with_environment<T: Into<String>>(mut self, environment: T) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Path of the overlay of a file or folder, relative to the root folder
+    fn overlay(&self, name: &str) -> Option<String> {
+        self.environment
+            .as_ref()
+            .map(|environment| format!("overlays/{environment}/{name}"))
+    }
+
+    /// Load config from the root folder, or from a bundled file if the path points to a file
+    pub fn load_config<T: Into<String>>(self, root_folder: T) -> Result<JsonCfg, ConfigError> {
+        let root_folder = PathBuf::from(root_folder.into());
+        if root_folder.is_file() {
+            return self.load_bundle(&root_folder);
+        }
+        // A wrong root would otherwise load as an empty config, sub-folders are optional
+        if let Err(source) = std::fs::read_dir(&root_folder) {
+            return Err(ConfigError::ReadDir {
+                path: root_folder,
+                source,
+            });
+        }
+        self.load_layers(&[ConfigDir::Disk(&root_folder)])
+    }
+
+    /// Load config embedded in the binary, files in the overrides folder take precedence
+    /// over the embedded files of the same name, table config are overridden as a whole
+    #[cfg(feature = "embed")]
+    pub fn load_embedded<P: AsRef<Path>>(
+        self,
+        embedded: &include_dir::Dir<'_>,
+        overrides: Option<P>,
+    ) -> Result<JsonCfg, ConfigError> {
+        let mut layers = vec![ConfigDir::Embedded(embedded)];
+        if let Some(overrides) = overrides.as_ref().map(AsRef::as_ref) {
+            if overrides.is_dir() {
+                layers.push(ConfigDir::Disk(overrides));
+            }
+        }
+        self.load_layers(&layers)
+    }
 
+    /// Load config from folders stacked on top of each other, the last one takes precedence
+    pub fn load_layers(self, layers: &[ConfigDir<'_>]) -> Result<JsonCfg, ConfigError> {
         // Load site config
-        let content = std::fs::read_to_string(format!("{root_folder}/dashboard.toml"))
-            .unwrap_or(DEFAULT_DASHBOARD_CONFIG.into());
-        let dashboard_config: DashboardCfg = toml::from_str(&content)?;
+        let mut json_config: JsonCfg = self.load_file(layers, "config", DEFAULT_SITE_CONFIG)?;
+        json_config.sources.site =
+            find_file(layers, "config")?.map(|(layer, rel)| layer.display_path(&rel));
+
+        // Load dashboard config
+        let dashboard_config: DashboardCfg =
+            self.load_file(layers, "dashboard", DEFAULT_DASHBOARD_CONFIG)?;
         json_config.dashboard = dashboard_config;
 
+        // Load named dashboards
+        for (name, (source, dashboard)) in self.load_tables::<DashboardCfg>(layers, "dashboards")? {
+            json_config.dashboards.insert(name.clone(), dashboard);
+            json_config
+                .sources
+                .dashboards
+                .insert(name, TableSource::file(source));
+        }
+
         // Load raw table config
-        let walkdir = walkdir::WalkDir::new(format!("{root_folder}/raw_tables"));
-        for file in walkdir.into_iter().filter_map(|e| e.ok()).skip(1) {
-            let file_name = file.file_name().to_str().unwrap();
-            let (name, _ext) = file_name.split_once('.').unwrap();
-            let content = std::fs::read_to_string(file.path())?;
-            let raw_table: RawTableCfg = toml::from_str(&content)?;
-            json_config.raw_tables.insert(name.into(), raw_table);
+        for (name, (source, raw_table)) in self.load_tables::<RawTableCfg>(layers, "raw_tables")? {
+            json_config.raw_tables.insert(name.clone(), raw_table);
+            json_config
+                .sources
+                .raw_tables
+                .insert(name, TableSource::file(source));
         }
 
         // Load composite table config
-        let walkdir = walkdir::WalkDir::new(format!("{root_folder}/composite_tables"));
-        for file in walkdir.into_iter().filter_map(|e| e.ok()).skip(1) {
-            let file_name = file.file_name().to_str().unwrap();
-            let (name, _ext) = file_name.split_once('.').unwrap();
-            let content = std::fs::read_to_string(file.path())?;
-            let composite_table: CompositeTableCfg = toml::from_str(&content)?;
+        for (name, (source, composite_table)) in
+            self.load_tables::<CompositeTableCfg>(layers, "composite_tables")?
+        {
+            json_config
+                .composite_tables
+                .insert(name.clone(), composite_table);
             json_config
+                .sources
                 .composite_tables
-                .insert(name.into(), composite_table);
+                .insert(name, TableSource::file(source));
+        }
+
+        Ok(json_config)
+    }
+
+    /// Load the config file named `name` merged with its overlay,
+    /// fallback to the default config if the file doesn't exist
+    fn load_file<T: DeserializeOwned>(
+        &self,
+        layers: &[ConfigDir<'_>],
+        name: &str,
+        default: &str,
+    ) -> Result<T, ConfigError> {
+        let overlay = match self.overlay(name) {
+            Some(overlay) => find_file(layers, &overlay)?,
+            None => None,
+        };
+        match (find_file(layers, name)?, overlay) {
+            (Some((layer, rel)), None) => parse_file(layer, &rel),
+            (None, None) => Ok(parse_default(default)),
+            (base, Some((layer, rel))) => {
+                let mut value = match base {
+                    Some((layer, rel)) => parse_file(layer, &rel)?,
+                    None => parse_default(default),
+                };
+                merge(&mut value, parse_file(layer, &rel)?);
+                from_value(&layer.display_path(&rel), value)
+            }
+        }
+    }
+
+    /// Load the table config in the sub-folder merged with their overlays, keyed by table name,
+    /// along with the path of the file defining the table
+    #[allow(clippy::type_complexity)]
+    fn load_tables<T: DeserializeOwned>(
+        &self,
+        layers: &[ConfigDir<'_>],
+        folder: &str,
+    ) -> Result<IndexMap<String, (PathBuf, T)>, ConfigError> {
+        let mut overlays = match self.overlay(folder) {
+            Some(overlay) => table_files(layers, &overlay)?,
+            None => IndexMap::new(),
+        };
+        let mut tables = IndexMap::new();
+        for (name, (layer, rel)) in table_files(layers, folder)? {
+            let path = layer.display_path(&rel);
+            let table = match overlays.shift_remove(&name) {
+                Some((overlay_layer, overlay_rel)) => {
+                    let mut value = parse_file(layer, &rel)?;
+                    merge(&mut value, parse_file(overlay_layer, &overlay_rel)?);
+                    from_value(&overlay_layer.display_path(&overlay_rel), value)?
+                }
+                None => parse_file(layer, &rel)?,
+            };
+            tables.insert(name, (path, table));
+        }
+        // Tables only exist in the overlay
+        for (name, (layer, rel)) in overlays {
+            let table = parse_file(layer, &rel)?;
+            tables.insert(name, (layer.display_path(&rel), table));
+        }
+        Ok(tables)
+    }
+
+    /// Load config from a single file containing the site, dashboard and table config,
+    /// site and dashboard config fallback to the defaults as in the folder layout.
+    /// The overlay of the environment is a bundle of the same name in `overlays/<environment>/`
+    /// next to the file, e.g. `overlays/production/admin.toml` for `admin.toml`.
+    pub fn load_bundle<P: AsRef<Path>>(self, path: P) -> Result<JsonCfg, ConfigError> {
+        #[derive(Deserialize)]
+        struct BundleCfg {
+            site: Option<SiteCfg>,
+            dashboard: Option<DashboardCfg>,
+            #[serde(default)]
+            dashboards: IndexMap<String, DashboardCfg>,
+            #[serde(default)]
+            raw_tables: IndexMap<String, RawTableCfg>,
+            #[serde(default)]
+            composite_tables: IndexMap<String, CompositeTableCfg>,
         }
 
+        let path = path.as_ref();
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        let (root, file_name, stem) = match (path.parent(), path.file_name(), stem) {
+            (Some(root), Some(file_name), Some(stem)) => (root, Path::new(file_name), stem),
+            _ => return Err(ConfigError::InvalidFileName { path: path.into() }),
+        };
+        let layer = ConfigDir::Disk(root);
+        let overlay = match self.overlay(stem) {
+            Some(overlay) => find_file(&[layer], &overlay)?,
+            None => None,
+        };
+        let bundle: BundleCfg = match overlay {
+            Some((overlay_layer, overlay_rel)) => {
+                let mut value = parse_file(layer, file_name)?;
+                merge(&mut value, parse_file(overlay_layer, &overlay_rel)?);
+                from_value(&overlay_layer.display_path(&overlay_rel), value)?
+            }
+            None => parse_file(layer, file_name)?,
+        };
+        let mut json_config = JsonCfg {
+            site: match bundle.site {
+                Some(site) => site,
+                None => parse_default::<JsonCfg>(DEFAULT_SITE_CONFIG).site,
+            },
+            dashboard: match bundle.dashboard {
+                Some(dashboard) => dashboard,
+                None => parse_default(DEFAULT_DASHBOARD_CONFIG),
+            },
+            dashboards: bundle.dashboards,
+            raw_tables: bundle.raw_tables,
+            composite_tables: bundle.composite_tables,
+            sources: Default::default(),
+        };
+        json_config.sources.site = Some(path.into());
+        for name in json_config.raw_tables.keys() {
+            json_config.sources.raw_tables.insert(
+                name.clone(),
+                TableSource::bundle(path.into(), format!("raw_tables.{name}")),
+            );
+        }
+        for name in json_config.composite_tables.keys() {
+            json_config.sources.composite_tables.insert(
+                name.clone(),
+                TableSource::bundle(path.into(), format!("composite_tables.{name}")),
+            );
+        }
+        for name in json_config.dashboards.keys() {
+            json_config.sources.dashboards.insert(
+                name.clone(),
+                TableSource::bundle(path.into(), format!("dashboards.{name}")),
+            );
+        }
         Ok(json_config)
     }
 }
 
+/// Parse a config file according to its extension, with `${VAR}` substituted
+fn parse_file<T: DeserializeOwned>(layer: ConfigDir<'_>, rel: &Path) -> Result<T, ConfigError> {
+    let path = layer.display_path(rel);
+    let Some(format) = ConfigFormat::from_path(rel) else {
+        return Err(ConfigError::UnsupportedFile { path });
+    };
+    let content = layer.read(rel)?;
+    if !content.contains("${") {
+        // Parse straight into the config structure to keep the location of errors
+        return format.parse(&path, &content);
+    }
+    let mut value: Value = format.parse(&path, &content)?;
+    interpolate(&path, String::new(), &mut value)?;
+    from_value(&path, value)
+}
+
+/// Deserialize the config structure from a parsed config file
+fn from_value<T: DeserializeOwned>(path: &Path, value: Value) -> Result<T, ConfigError> {
+    serde_json::from_value(value)
+        .map_err(|err| ConfigError::parse(path.into(), "", &err.to_string(), None))
+}
+
+/// Deep merge the overlay into the base value, tables are merged key by key,
+/// everything else in the overlay replaces the base value
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Substitute `${VAR}` in all string values with the environment variable
+fn interpolate(path: &Path, key: String, value: &mut Value) -> Result<(), ConfigError> {
+    match value {
+        Value::String(string) if string.contains("${") => {
+            let error = |message: String| ConfigError::Interpolation {
+                path: path.into(),
+                key: key.clone(),
+                message,
+            };
+            let mut result = String::with_capacity(string.len());
+            let mut rest = string.as_str();
+            while let Some(start) = rest.find("${") {
+                result.push_str(&rest[..start]);
+                let Some(len) = rest[start + 2..].find('}') else {
+                    return Err(error("unterminated `${`".into()));
+                };
+                let name = &rest[start + 2..start + 2 + len];
+                match std::env::var(name) {
+                    Ok(var) => result.push_str(&var),
+                    Err(_) => {
+                        return Err(error(format!("environment variable `{name}` is not set")))
+                    }
+                }
+                rest = &rest[start + 3 + len..];
+            }
+            result.push_str(rest);
+            *string = result;
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                interpolate(path, format!("{key}[{i}]"), value)?;
+            }
+        }
+        Value::Object(values) => {
+            for (name, value) in values.iter_mut() {
+                let key = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{key}.{name}")
+                };
+                interpolate(path, key, value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parse the built-in default config
+fn parse_default<T: DeserializeOwned>(content: &str) -> T {
+    toml::from_str(content).expect("default config is valid")
+}
+
+/// Find the config file named `name` in any of the supported formats, from the top layer
+fn find_file<'a>(
+    layers: &[ConfigDir<'a>],
+    name: &str,
+) -> Result<Option<(ConfigDir<'a>, PathBuf)>, ConfigError> {
+    for layer in layers.iter().rev() {
+        let mut found: Option<PathBuf> = None;
+        for ext in ConfigFormat::EXTENSIONS {
+            let rel = PathBuf::from(format!("{name}.{ext}"));
+            if !layer.is_file(&rel) {
+                continue;
+            }
+            if let Some(first) = found {
+                return Err(ConfigError::DuplicateFile {
+                    first: layer.display_path(&first),
+                    second: layer.display_path(&rel),
+                });
+            }
+            found = Some(rel);
+        }
+        if let Some(rel) = found {
+            return Ok(Some((*layer, rel)));
+        }
+    }
+    Ok(None)
+}
+
+/// Collect table config files in the sub-folder keyed by table name, i.e. the file stem,
+/// tables in the upper layer replace the ones in the lower layers
+#[allow(clippy::type_complexity)]
+fn table_files<'a>(
+    layers: &[ConfigDir<'a>],
+    folder: &str,
+) -> Result<IndexMap<String, (ConfigDir<'a>, PathBuf)>, ConfigError> {
+    let mut tables = IndexMap::new();
+    for layer in layers.iter() {
+        let mut files = IndexMap::<String, PathBuf>::new();
+        for rel in layer.files(Path::new(folder))? {
+            if ConfigFormat::from_path(&rel).is_none() {
+                return Err(ConfigError::UnsupportedFile {
+                    path: layer.display_path(&rel),
+                });
+            }
+            let Some(name) = rel.file_stem().and_then(|s| s.to_str()) else {
+                return Err(ConfigError::InvalidFileName {
+                    path: layer.display_path(&rel),
+                });
+            };
+            if let Some(first) = files.get(name) {
+                return Err(ConfigError::DuplicateTable {
+                    name: name.into(),
+                    first: layer.display_path(first),
+                    second: layer.display_path(&rel),
+                });
+            }
+            files.insert(name.into(), rel);
+        }
+        for (name, rel) in files {
+            tables.insert(name, (*layer, rel));
+        }
+    }
+    Ok(tables)
+}
+
 const DEFAULT_SITE_CONFIG: &str = r#"
 [site.theme]
 title = "SeaORM Pro"
@@ -0,0 +1,223 @@
+use crate::{
+    config::{JsonCfg, MenuNodeCfg, RawTableCfg},
+    meta::EntityMeta,
+};
+
+/// Part of the page of a table a column is shown on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableView {
+    /// Columns of the view table, the columns to sort by
+    Table,
+    /// Filter panel, the columns to filter by
+    Filter,
+}
+
+/// Action on a table, the `act` of an access policy `(subject, table, action)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableAction {
+    Read,
+    Create,
+    Update,
+    Delete,
+}
+
+impl TableAction {
+    /// Name of the action in the access policies
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl JsonCfg {
+    /// Config as seen by a user, `allow` decides whether the user may take an action on a table
+    /// and `subjects` are the user and their roles, keys of the column permissions.
+    ///
+    /// Tables the user can't read are dropped, create / update / delete are only enabled
+    /// if allowed, the columns are hidden or made readonly by the permissions of the subjects
+    /// and dashboards and menu entries of other roles are left out.
+    pub fn permitted<F>(&self, entities: &[EntityMeta], subjects: &[String], allow: F) -> JsonCfg
+    where
+        F: Fn(&str, TableAction) -> bool,
+    {
+        let mut cfg = self.clone();
+        cfg.raw_tables
+            .retain(|table, _| allow(table, TableAction::Read));
+        for (table, raw_table) in cfg.raw_tables.iter_mut() {
+            restrict(raw_table, table, subjects, &allow);
+        }
+
+        cfg.composite_tables
+            .retain(|_, composite| allow(&composite.parent.name, TableAction::Read));
+        for composite in cfg.composite_tables.values_mut() {
+            let parent = &mut composite.parent;
+            restrict(&mut parent.parent_config, &parent.name, subjects, &allow);
+            let entity = entities
+                .iter()
+                .find(|entity| entity.table_name == parent.name);
+            // Child tables are named by the relation of the parent
+            composite.children.retain_mut(|child| {
+                let table = entity
+                    .and_then(|entity| entity.relation(&child.relation))
+                    .map(|relation| relation.to_table.as_str());
+                match table {
+                    Some(table) if !allow(table, TableAction::Read) => false,
+                    Some(table) => {
+                        restrict(&mut child.child_config, table, subjects, &allow);
+                        true
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        let permitted =
+            |roles: &[String]| roles.is_empty() || roles.iter().any(|role| subjects.contains(role));
+        cfg.dashboards
+            .retain(|_, dashboard| permitted(&dashboard.roles));
+        if !permitted(&cfg.dashboard.roles) {
+            // Default dashboard is always there, only its content is left out
+            cfg.dashboard.info = None;
+            cfg.dashboard.row.clear();
+        }
+        for dashboard in std::iter::once(&mut cfg.dashboard).chain(cfg.dashboards.values_mut()) {
+            dashboard.roles.clear();
+            // Data points don't open tables the user can't read
+            for chart in dashboard.charts_mut() {
+                let table = chart
+                    .drill_down
+                    .as_ref()
+                    .map(|drill_down| &drill_down.table);
+                if table.is_some_and(|table| !cfg.raw_tables.contains_key(table)) {
+                    chart.drill_down = None;
+                }
+            }
+        }
+
+        let items = std::mem::take(&mut cfg.site.menu.items);
+        cfg.site.menu.items = menu_items(&cfg, items, subjects);
+        cfg
+    }
+}
+
+impl JsonCfg {
+    /// Whether a config of the table shows the column or virtual column on the view, i.e. lists it
+    /// or shows all columns, and doesn't hide it. Secret columns such as password hashes never are.
+    ///
+    /// Meant for the config as seen by a user, see [`JsonCfg::permitted`], to tell the columns
+    /// the user may filter or sort by.
+    pub fn shows_column(
+        &self,
+        entities: &[EntityMeta],
+        table: &str,
+        field: &str,
+        view: TableView,
+    ) -> bool {
+        let column = entities
+            .iter()
+            .find(|entity| entity.table_name == table)
+            .and_then(|entity| entity.column(field));
+        if column.is_some_and(|column| column.is_secret()) {
+            return false;
+        }
+        self.table_configs(entities, table).any(|cfg| {
+            let hidden = match view {
+                TableView::Table => &cfg.table.hidden_columns,
+                TableView::Filter => &cfg.filter.hidden_columns,
+            };
+            let listed = cfg.table.virtual_columns.iter().any(|c| c.field == field)
+                || column.is_some()
+                    && (cfg.table.all_columns
+                        || cfg
+                            .table
+                            .columns
+                            .iter()
+                            .any(|c| c.relation.is_none() && c.field == field));
+            listed && !hidden.iter().any(|c| c == field)
+        })
+    }
+}
+
+/// Menu entries the user can see, groups left empty are dropped
+fn menu_items(cfg: &JsonCfg, items: Vec<MenuNodeCfg>, subjects: &[String]) -> Vec<MenuNodeCfg> {
+    items
+        .into_iter()
+        .filter_map(|mut item| {
+            if !item.roles.is_empty() && !item.roles.iter().any(|role| subjects.contains(role)) {
+                return None;
+            }
+            // Tables and dashboards the user can't read are already dropped
+            let dropped = item
+                .dashboard
+                .as_ref()
+                .is_some_and(|name| cfg.find_dashboard(Some(name)).is_none())
+                || item
+                    .raw_table
+                    .as_ref()
+                    .is_some_and(|table| !cfg.raw_tables.contains_key(table))
+                || item
+                    .composite_table
+                    .as_ref()
+                    .is_some_and(|table| !cfg.composite_tables.contains_key(table));
+            if dropped {
+                return None;
+            }
+            if !item.children.is_empty() {
+                item.children = menu_items(cfg, std::mem::take(&mut item.children), subjects);
+                if item.children.is_empty() {
+                    return None;
+                }
+            }
+            item.roles.clear();
+            Some(item)
+        })
+        .collect()
+}
+
+/// Apply the decisions and the column permissions to the table config
+fn restrict<F>(cfg: &mut RawTableCfg, table: &str, subjects: &[String], allow: &F)
+where
+    F: Fn(&str, TableAction) -> bool,
+{
+    cfg.create.enable &= allow(table, TableAction::Create);
+    cfg.update.enable &= allow(table, TableAction::Update);
+    cfg.delete.enable &= allow(table, TableAction::Delete);
+
+    // Permissions of other subjects are none of the user's business
+    let permissions = std::mem::take(&mut cfg.permissions);
+    for permission in subjects
+        .iter()
+        .filter_map(|subject| permissions.get(subject))
+    {
+        for column in permission.hidden_columns.iter() {
+            cfg.table
+                .columns
+                .retain(|c| c.relation.is_some() || c.field != *column);
+            cfg.table.virtual_columns.retain(|c| c.field != *column);
+            cfg.editor.fields.retain(|f| f.field != *column);
+            for columns in [
+                &mut cfg.table.hidden_columns,
+                &mut cfg.filter.hidden_columns,
+                &mut cfg.create.hidden_columns,
+                &mut cfg.update.hidden_columns,
+            ] {
+                push_unique(columns, column);
+            }
+        }
+        for column in permission.readonly_columns.iter() {
+            // Values of new rows can't be set either
+            push_unique(&mut cfg.create.hidden_columns, column);
+            push_unique(&mut cfg.update.readonly_columns, column);
+        }
+    }
+}
+
+fn push_unique(columns: &mut Vec<String>, column: &str) {
+    if !columns.iter().any(|c| c == column) {
+        columns.push(column.to_owned());
+    }
+}
@@ -0,0 +1,56 @@
+use schemars::{schema_for, Schema};
+
+use crate::config::{CompositeTableCfg, DashboardCfg, JsonCfg, RawTableCfg};
+
+/// Config files described by a JSON Schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSchema {
+    /// Site config, `config.toml`, also the shape of the config served to the frontend
+    Config,
+    /// Dashboard config, `dashboard.toml` and `dashboards/*.toml`
+    Dashboard,
+    /// Raw table config, `raw_tables/*.toml`
+    RawTable,
+    /// Composite table config, `composite_tables/*.toml`
+    CompositeTable,
+}
+
+impl ConfigSchema {
+    /// All config schemas
+    pub const ALL: [Self; 4] = [
+        Self::Config,
+        Self::Dashboard,
+        Self::RawTable,
+        Self::CompositeTable,
+    ];
+
+    /// Name of the schema, e.g. `raw_table`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Dashboard => "dashboard",
+            Self::RawTable => "raw_table",
+            Self::CompositeTable => "composite_table",
+        }
+    }
+
+    /// Find schema by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|schema| schema.name() == name)
+    }
+
+    /// File name of the exported schema, e.g. `raw_table.schema.json`
+    pub fn file_name(self) -> String {
+        format!("{}.schema.json", self.name())
+    }
+
+    /// JSON Schema of the config, doc comments of the config structs are the descriptions
+    pub fn schema(self) -> Schema {
+        match self {
+            Self::Config => schema_for!(JsonCfg),
+            Self::Dashboard => schema_for!(DashboardCfg),
+            Self::RawTable => schema_for!(RawTableCfg),
+            Self::CompositeTable => schema_for!(CompositeTableCfg),
+        }
+    }
+}
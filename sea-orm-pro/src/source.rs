@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::ConfigError;
+
+/// A folder of config files, either on the disk or embedded in the binary
+#[derive(Debug, Clone, Copy)]
+pub enum ConfigDir<'a> {
+    /// Folder on the disk
+    Disk(&'a Path),
+    /// Folder embedded in the binary with `include_dir!`
+    #[cfg(feature = "embed")]
+    Embedded(&'a include_dir::Dir<'a>),
+}
+
+impl ConfigDir<'_> {
+    /// Path of the file for display, `rel` is relative to the folder
+    pub fn display_path(&self, rel: &Path) -> PathBuf {
+        match self {
+            Self::Disk(root) => root.join(rel),
+            #[cfg(feature = "embed")]
+            Self::Embedded(_) => Path::new("<embedded>").join(rel),
+        }
+    }
+
+    /// Does the file exist
+    pub fn is_file(&self, rel: &Path) -> bool {
+        match self {
+            Self::Disk(root) => root.join(rel).is_file(),
+            #[cfg(feature = "embed")]
+            Self::Embedded(dir) => dir.get_file(rel).is_some(),
+        }
+    }
+
+    /// Read the content of the file
+    pub fn read(&self, rel: &Path) -> Result<String, ConfigError> {
+        match self {
+            Self::Disk(root) => {
+                let path = root.join(rel);
+                std::fs::read_to_string(&path)
+                    .map_err(|source| ConfigError::ReadFile { path, source })
+            }
+            #[cfg(feature = "embed")]
+            Self::Embedded(dir) => {
+                let read_error = |kind, msg| ConfigError::ReadFile {
+                    path: self.display_path(rel),
+                    source: std::io::Error::new(kind, msg),
+                };
+                let file = dir
+                    .get_file(rel)
+                    .ok_or_else(|| read_error(std::io::ErrorKind::NotFound, "file not found"))?;
+                file.contents_utf8()
+                    .map(Into::into)
+                    .ok_or_else(|| read_error(std::io::ErrorKind::InvalidData, "not UTF-8"))
+            }
+        }
+    }
+
+    /// All files under the sub-folder sorted by path, relative to the folder,
+    /// hidden files such as editor swap files are skipped
+    pub fn files(&self, folder: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+        let is_hidden = |path: &Path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        };
+        match self {
+            Self::Disk(root) => {
+                let mut files = Vec::new();
+                let folder = root.join(folder);
+                if !folder.exists() {
+                    return Ok(files);
+                }
+                let walkdir = walkdir::WalkDir::new(&folder)
+                    .min_depth(1)
+                    .sort_by_file_name();
+                for entry in walkdir {
+                    let entry = entry.map_err(|source| ConfigError::ReadDir {
+                        path: source.path().unwrap_or(&folder).to_owned(),
+                        source: source.into(),
+                    })?;
+                    if entry.file_type().is_file() && !is_hidden(entry.path()) {
+                        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                        files.push(rel.to_owned());
+                    }
+                }
+                Ok(files)
+            }
+            #[cfg(feature = "embed")]
+            Self::Embedded(dir) => {
+                fn collect<'a>(dir: &include_dir::Dir<'a>, files: &mut Vec<PathBuf>) {
+                    for entry in dir.entries() {
+                        match entry {
+                            include_dir::DirEntry::Dir(dir) => collect(dir, files),
+                            include_dir::DirEntry::File(file) => files.push(file.path().into()),
+                        }
+                    }
+                }
+                let mut files = Vec::new();
+                if let Some(dir) = dir.get_dir(folder) {
+                    collect(dir, &mut files);
+                }
+                files.retain(|path| !is_hidden(path));
+                files.sort();
+                Ok(files)
+            }
+        }
+    }
+}
@@ -0,0 +1,860 @@
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Serialize;
+use std::fmt;
+
+use crate::{
+    config::{
+        ChartKind, DashboardCfg, FilterConditionCfg, InputType, JsonCfg, MenuNodeCfg, RawTableCfg,
+        RulesCfg, SelectOptions, TableSource, DEFAULT_DASHBOARD,
+    },
+    expr::ColumnExpr,
+    input::parse_value,
+    meta::{ColumnMeta, EntityMeta},
+};
+
+/// A config entry that does not match the registered entities
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// Config file containing the error
+    pub file: String,
+    /// TOML path of the offending value, e.g. `table.columns[1].field`
+    pub path: String,
+    /// Description of the error
+    pub message: String,
+}
+
+/// All errors found while validating the config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(f, "{}: `{}`: {}", self.file, self.path, self.message)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} error(s) found in admin panel config", self.0.len())?;
+        for error in self.0.iter() {
+            writeln!(f, "  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl JsonCfg {
+    /// Cross-check every table, column and relation mentioned in the config against the entities
+    pub fn validate(&self, entities: &[EntityMeta]) -> Result<(), ValidationErrors> {
+        let mut validator = Validator {
+            entities,
+            errors: Vec::new(),
+        };
+
+        for (name, raw_table) in self.raw_tables.iter() {
+            let (file, prefix) = source(&self.sources.raw_tables, "raw_tables", name);
+            match validator.entity(name) {
+                Some(entity) => validator.raw_table(&file, &prefix, entity, raw_table),
+                None => validator.error(
+                    &file,
+                    prefix.trim_end_matches('.'),
+                    format!("table `{name}` does not exist"),
+                ),
+            }
+        }
+
+        for (name, composite_table) in self.composite_tables.iter() {
+            let (file, prefix) = source(&self.sources.composite_tables, "composite_tables", name);
+            let parent = &composite_table.parent;
+            let Some(entity) = validator.entity(&parent.name) else {
+                validator.error(
+                    &file,
+                    format!("{prefix}parent.name"),
+                    format!("table `{}` does not exist", parent.name),
+                );
+                continue;
+            };
+            validator.raw_table(
+                &file,
+                &format!("{prefix}parent."),
+                entity,
+                &parent.parent_config,
+            );
+
+            for (i, child) in composite_table.children.iter().enumerate() {
+                let prefix = format!("{prefix}children[{i}].");
+                let Some(relation) = entity.relation(&child.relation) else {
+                    validator.error(
+                        &file,
+                        format!("{prefix}relation"),
+                        format!(
+                            "relation `{}` does not exist on table `{}`",
+                            child.relation, entity.table_name
+                        ),
+                    );
+                    continue;
+                };
+                match validator.entity(&relation.to_table) {
+                    Some(child_entity) => {
+                        validator.raw_table(&file, &prefix, child_entity, &child.child_config)
+                    }
+                    None => validator.error(
+                        &file,
+                        format!("{prefix}relation"),
+                        format!("table `{}` does not exist", relation.to_table),
+                    ),
+                }
+            }
+        }
+
+        validator.dashboard("dashboard", "", &self.dashboard, &self.raw_tables);
+        for (name, dashboard) in self.dashboards.iter() {
+            let (file, prefix) = source(&self.sources.dashboards, "dashboards", name);
+            validator.dashboard(&file, &prefix, dashboard, &self.raw_tables);
+        }
+        if self.dashboards.contains_key(DEFAULT_DASHBOARD) {
+            let (file, prefix) = source(&self.sources.dashboards, "dashboards", DEFAULT_DASHBOARD);
+            validator.error(
+                &file,
+                prefix.trim_end_matches('.'),
+                format!("`{DEFAULT_DASHBOARD}` is the name of the dashboard in `dashboard.toml`"),
+            );
+        }
+
+        for (i, item) in self.site.menu.items.iter().enumerate() {
+            self.menu_item(&mut validator, format!("site.menu.items[{i}]"), item);
+        }
+
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(validator.errors))
+        }
+    }
+}
+
+impl JsonCfg {
+    /// Check the entry links to exactly one page, or is a group, and the tables exist
+    fn menu_item(&self, validator: &mut Validator<'_>, path: String, item: &MenuNodeCfg) {
+        let file = self.sources.site_file();
+        let file = file.as_str();
+        let targets = [
+            item.dashboard.is_some(),
+            item.raw_table.is_some(),
+            item.composite_table.is_some(),
+            item.link.is_some(),
+            !item.children.is_empty(),
+        ];
+        if targets.iter().filter(|target| **target).count() != 1 {
+            validator.error(
+                file,
+                path.as_str(),
+                "menu entry needs exactly one of `dashboard`, `raw_table`, `composite_table`, `link` or `children`",
+            );
+        }
+        if let Some(name) = &item.dashboard {
+            if self.find_dashboard(Some(name)).is_none() {
+                validator.error(
+                    file,
+                    format!("{path}.dashboard"),
+                    format!("dashboard `{name}` does not exist"),
+                );
+            }
+        }
+        if let Some(table) = &item.raw_table {
+            if !self.raw_tables.contains_key(table) {
+                validator.error(
+                    file,
+                    format!("{path}.raw_table"),
+                    format!("raw table `{table}` does not exist"),
+                );
+            }
+        }
+        if let Some(table) = &item.composite_table {
+            if !self.composite_tables.contains_key(table) {
+                validator.error(
+                    file,
+                    format!("{path}.composite_table"),
+                    format!("composite table `{table}` does not exist"),
+                );
+            }
+        }
+        // Pages have a title to fall back to, groups and links don't
+        if item.title.is_none() && (item.link.is_some() || !item.children.is_empty()) {
+            validator.error(file, format!("{path}.title"), "title is required");
+        }
+        for (i, child) in item.children.iter().enumerate() {
+            self.menu_item(validator, format!("{path}.children[{i}]"), child);
+        }
+    }
+}
+
+/// File name and path prefix of the table config
+fn source(sources: &IndexMap<String, TableSource>, folder: &str, name: &str) -> (String, String) {
+    match sources.get(name) {
+        Some(source) if source.path.is_empty() => {
+            (source.file.display().to_string(), String::new())
+        }
+        Some(source) => (
+            source.file.display().to_string(),
+            format!("{}.", source.path),
+        ),
+        None => (format!("{folder}/{name}"), String::new()),
+    }
+}
+
+struct Validator<'a> {
+    entities: &'a [EntityMeta],
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn entity(&self, table_name: &str) -> Option<&'a EntityMeta> {
+        self.entities
+            .iter()
+            .find(|entity| entity.table_name == table_name)
+    }
+
+    fn error(&mut self, file: &str, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            file: file.into(),
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn column(&mut self, file: &str, path: String, entity: &EntityMeta, column: &str) {
+        if entity.column(column).is_none() {
+            self.error(
+                file,
+                path,
+                format!(
+                    "column `{column}` does not exist on table `{}`",
+                    entity.table_name
+                ),
+            );
+        }
+    }
+
+    fn dashboard(
+        &mut self,
+        file: &str,
+        prefix: &str,
+        dashboard: &DashboardCfg,
+        raw_tables: &IndexMap<String, RawTableCfg>,
+    ) {
+        for (i, row) in dashboard.row.iter().enumerate() {
+            for (j, col) in row.col.iter().enumerate() {
+                let Some(chart) = &col.chart else {
+                    continue;
+                };
+                let path = format!("{prefix}row[{i}].col[{j}].chart");
+                if let Some(Err(message)) = chart.time_bucket() {
+                    self.error(file, format!("{path}.timescale"), message);
+                }
+                if let Some(Err(message)) = chart.default_date_range() {
+                    self.error(file, format!("{path}.default_date_range"), message);
+                }
+                if let Some(drill_down) = &chart.drill_down {
+                    // Data points open the raw table, not just any table
+                    if let Err(err) = drill_down.check(self.entities, chart.query.as_ref()) {
+                        self.error(file, format!("{path}.drill_down.{}", err.path), err.message);
+                    } else if !raw_tables.contains_key(&drill_down.table) {
+                        self.error(
+                            file,
+                            format!("{path}.drill_down.table"),
+                            format!("raw table `{}` does not exist", drill_down.table),
+                        );
+                    }
+                }
+                let Some(query) = &chart.query else {
+                    continue;
+                };
+                if let Err(err) = query.check(self.entities) {
+                    self.error(file, format!("{path}.query.{}", err.path), err.message);
+                }
+                if query.key.is_none() && chart.kind != ChartKind::Stat {
+                    self.error(
+                        file,
+                        format!("{path}.query.key"),
+                        "only `stat` charts go without a key",
+                    );
+                }
+                for (k, name) in chart.secondary_axis.iter().enumerate() {
+                    if !query.series.iter().any(|series| series.name == *name) {
+                        self.error(
+                            file,
+                            format!("{path}.secondary_axis[{k}]"),
+                            format!("series `{name}` does not exist on the chart"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn columns(&mut self, file: &str, path: String, entity: &EntityMeta, columns: &[String]) {
+        for (i, column) in columns.iter().enumerate() {
+            self.column(file, format!("{path}[{i}]"), entity, column);
+        }
+    }
+
+    fn conditions(
+        &mut self,
+        file: &str,
+        path: String,
+        entity: &EntityMeta,
+        conditions: &[FilterConditionCfg],
+    ) {
+        for (i, condition) in conditions.iter().enumerate() {
+            if let Err(message) = condition.check(entity) {
+                self.error(file, format!("{path}[{i}]"), message);
+            }
+        }
+    }
+
+    fn input_type(
+        &mut self,
+        file: &str,
+        path: &str,
+        entity: &EntityMeta,
+        input_type: &Option<InputType>,
+    ) {
+        let Some(InputType::RelationPicker {
+            relation: Some(relation_name),
+            label_field,
+        }) = input_type
+        else {
+            return;
+        };
+        let path = format!("{path}.input_type");
+        let Some(relation) = entity.relation(relation_name) else {
+            self.error(
+                file,
+                format!("{path}.relation"),
+                format!(
+                    "relation `{relation_name}` does not exist on table `{}`",
+                    entity.table_name
+                ),
+            );
+            return;
+        };
+        if let (Some(related), Some(label_field)) = (self.entity(&relation.to_table), label_field) {
+            self.column(file, format!("{path}.label_field"), related, label_field);
+        }
+    }
+
+    fn options(
+        &mut self,
+        file: &str,
+        path: &str,
+        column: &ColumnMeta,
+        options: &Option<SelectOptions>,
+        input_type: &Option<InputType>,
+    ) {
+        let options = [
+            options
+                .as_ref()
+                .map(|options| (format!("{path}.options"), options)),
+            match input_type {
+                Some(InputType::Select { options } | InputType::MultiSelect { options }) => {
+                    Some((format!("{path}.input_type.options"), options))
+                }
+                _ => None,
+            },
+        ];
+        for (path, options) in options.into_iter().flatten() {
+            match options {
+                SelectOptions::List(_) => {}
+                SelectOptions::Enum { name } => {
+                    if column.enum_name.as_deref() != Some(name.as_str()) {
+                        self.error(
+                            file,
+                            format!("{path}.enum"),
+                            format!("column `{}` is not of enum `{name}`", column.name),
+                        );
+                    }
+                }
+                SelectOptions::Map(map) => {
+                    for value in map.keys() {
+                        if parse_value(column, value).is_none() {
+                            self.error(
+                                file,
+                                format!("{path}.{value}"),
+                                format!(
+                                    "`{value}` is not a valid value of column `{}`",
+                                    column.name
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn rules(&mut self, file: &str, path: &str, rules: &RulesCfg) {
+        if let Some(pattern) = &rules.pattern {
+            if Regex::new(pattern).is_err() {
+                self.error(
+                    file,
+                    format!("{path}.pattern"),
+                    format!("`{pattern}` is not a valid regular expression"),
+                );
+            }
+        }
+        if let (Some(min), Some(max)) = (rules.min_length, rules.max_length) {
+            if min > max {
+                self.error(
+                    file,
+                    format!("{path}.min_length"),
+                    format!("`min_length` is greater than `max_length` {max}"),
+                );
+            }
+        }
+        if let (Some(min), Some(max)) = (rules.min, rules.max) {
+            if min > max {
+                self.error(
+                    file,
+                    format!("{path}.min"),
+                    format!("`min` is greater than `max` {max}"),
+                );
+            }
+        }
+    }
+
+    fn raw_table(&mut self, file: &str, prefix: &str, entity: &EntityMeta, cfg: &RawTableCfg) {
+        for (i, column) in cfg.table.columns.iter().enumerate() {
+            let path = format!("{prefix}table.columns[{i}]");
+            match &column.relation {
+                Some(relation_name) => {
+                    let Some(relation) = entity.relation(relation_name) else {
+                        self.error(
+                            file,
+                            format!("{path}.relation"),
+                            format!(
+                                "relation `{relation_name}` does not exist on table `{}`",
+                                entity.table_name
+                            ),
+                        );
+                        continue;
+                    };
+                    match self.entity(&relation.to_table) {
+                        Some(related) => {
+                            self.column(file, format!("{path}.field"), related, &column.field);
+                            if let Some(meta) = related.column(&column.field) {
+                                self.options(
+                                    file,
+                                    &path,
+                                    meta,
+                                    &column.options,
+                                    &column.input_type,
+                                );
+                            }
+                        }
+                        None => self.error(
+                            file,
+                            format!("{path}.relation"),
+                            format!("table `{}` does not exist", relation.to_table),
+                        ),
+                    }
+                }
+                None => {
+                    self.column(file, format!("{path}.field"), entity, &column.field);
+                    if let Some(meta) = entity.column(&column.field) {
+                        self.options(file, &path, meta, &column.options, &column.input_type);
+                    }
+                }
+            }
+            self.input_type(file, &path, entity, &column.input_type);
+        }
+        for (i, column) in cfg.table.virtual_columns.iter().enumerate() {
+            let path = format!("{prefix}table.virtual_columns[{i}]");
+            if entity.column(&column.field).is_some() {
+                self.error(
+                    file,
+                    format!("{path}.field"),
+                    format!(
+                        "column `{}` already exists on table `{}`",
+                        column.field, entity.table_name
+                    ),
+                );
+            }
+            match ColumnExpr::parse(&column.expr) {
+                Ok(expr) => {
+                    for name in expr.columns() {
+                        self.column(file, format!("{path}.expr"), entity, name);
+                    }
+                }
+                Err(err) => self.error(file, format!("{path}.expr"), err.to_string()),
+            }
+        }
+        // Virtual columns can be hidden and sorted by like the columns of the table
+        let virtual_columns = &cfg.table.virtual_columns;
+        let is_virtual = |column: &String| virtual_columns.iter().any(|v| v.field == *column);
+        for (i, column) in cfg.table.hidden_columns.iter().enumerate() {
+            if !is_virtual(column) {
+                let path = format!("{prefix}table.hidden_columns[{i}]");
+                self.column(file, path, entity, column);
+            }
+        }
+        if let Some(order_by) = cfg
+            .table
+            .order_by
+            .as_ref()
+            .filter(|o| !is_virtual(&o.field))
+        {
+            self.column(
+                file,
+                format!("{prefix}table.order_by.field"),
+                entity,
+                &order_by.field,
+            );
+        }
+
+        for (i, column) in cfg.filter.hidden_columns.iter().enumerate() {
+            if !is_virtual(column) {
+                let path = format!("{prefix}filter.hidden_columns[{i}]");
+                self.column(file, path, entity, column);
+            }
+        }
+        self.conditions(
+            file,
+            format!("{prefix}filter.default"),
+            entity,
+            &cfg.filter.default,
+        );
+        for (i, preset) in cfg.filter.presets.iter().enumerate() {
+            let path = format!("{prefix}filter.presets[{i}]");
+            if cfg.filter.presets[..i]
+                .iter()
+                .any(|other| other.name == preset.name)
+            {
+                self.error(
+                    file,
+                    format!("{path}.name"),
+                    format!("preset `{}` is defined more than once", preset.name),
+                );
+            }
+            self.conditions(
+                file,
+                format!("{path}.conditions"),
+                entity,
+                &preset.conditions,
+            );
+        }
+
+        if let Some(title_field) = &cfg.editor.title_field {
+            self.column(
+                file,
+                format!("{prefix}editor.title_field"),
+                entity,
+                title_field,
+            );
+        }
+        for (i, field) in cfg.editor.fields.iter().enumerate() {
+            let path = format!("{prefix}editor.fields[{i}]");
+            self.column(file, format!("{path}.field"), entity, &field.field);
+            if let Some(meta) = entity.column(&field.field) {
+                self.options(file, &path, meta, &field.options, &field.input_type);
+            }
+            if let Some(rules) = &field.rules {
+                self.rules(file, &format!("{path}.rules"), rules);
+            }
+            self.input_type(file, &path, entity, &field.input_type);
+        }
+
+        self.columns(
+            file,
+            format!("{prefix}create.hidden_columns"),
+            entity,
+            &cfg.create.hidden_columns,
+        );
+        self.columns(
+            file,
+            format!("{prefix}update.hidden_columns"),
+            entity,
+            &cfg.update.hidden_columns,
+        );
+        self.columns(
+            file,
+            format!("{prefix}update.readonly_columns"),
+            entity,
+            &cfg.update.readonly_columns,
+        );
+        for (subject, permission) in cfg.permissions.iter() {
+            self.columns(
+                file,
+                format!("{prefix}permissions.{subject}.hidden_columns"),
+                entity,
+                &permission.hidden_columns,
+            );
+            self.columns(
+                file,
+                format!("{prefix}permissions.{subject}.readonly_columns"),
+                entity,
+                &permission.readonly_columns,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::{
+        meta::{ColumnKind, RelationKind, RelationMeta},
+        parser::ConfigParser,
+    };
+
+    const SITE: &str = r#"
+[site.theme]
+title = "Admin"
+logo = "/logo.png"
+login_banner = "/banner.png"
+
+[site.menu]
+dashboard = {}
+raw_table = {}
+composite_table = {}
+"#;
+
+    fn column(name: &str, kind: ColumnKind) -> ColumnMeta {
+        ColumnMeta {
+            name: name.to_owned(),
+            kind,
+            primary_key: name.ends_with("_id"),
+            ..Default::default()
+        }
+    }
+
+    fn entities() -> Vec<EntityMeta> {
+        vec![
+            EntityMeta {
+                table_name: "customer".to_owned(),
+                columns: vec![
+                    column("customer_id", ColumnKind::Integer),
+                    column("first_name", ColumnKind::String),
+                ],
+                relations: vec![RelationMeta {
+                    name: "customer_address".to_owned(),
+                    to_table: "customer_address".to_owned(),
+                    kind: RelationKind::HasMany,
+                    from_columns: vec!["customer_id".to_owned()],
+                    to_columns: vec!["customer_id".to_owned()],
+                    via: None,
+                }],
+            },
+            EntityMeta {
+                table_name: "customer_address".to_owned(),
+                columns: vec![
+                    column("customer_id", ColumnKind::Integer),
+                    column("address_id", ColumnKind::Integer),
+                ],
+                relations: Vec::new(),
+            },
+        ]
+    }
+
+    /// Config folder in the temporary folder with the files, named after the test
+    fn config_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "sea-orm-pro-validator-{}-{test}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        for (name, content) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn validate(test: &str, files: &[(&str, &str)]) -> (PathBuf, Vec<ValidationError>) {
+        let root = config_dir(test, files);
+        let config = ConfigParser::new()
+            .load_config(root.display().to_string())
+            .unwrap();
+        let errors = match config.validate(&entities()) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.0,
+        };
+        fs::remove_dir_all(&root).unwrap();
+        (root, errors)
+    }
+
+    fn error(file: PathBuf, path: &str, message: &str) -> ValidationError {
+        ValidationError {
+            file: file.display().to_string(),
+            path: path.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn valid_config() {
+        let (_, errors) = validate(
+            "valid",
+            &[(
+                "raw_tables/customer.toml",
+                "[table]\ncolumns = [{ field = \"first_name\" }]\nhidden_columns = [\"customer_id\"]\n",
+            )],
+        );
+        assert_eq!(errors, []);
+    }
+
+    #[test]
+    fn virtual_columns_are_columns_of_the_table() {
+        let table = r#"
+[table]
+virtual_columns = [{ field = "full_name", expr = "first_name || ' ' || first_name" }]
+hidden_columns = ["full_name", "nickname"]
+order_by = { field = "full_name", order = "asc" }
+
+[filter]
+hidden_columns = ["full_name"]
+"#;
+        let (root, errors) = validate("virtual_columns", &[("raw_tables/customer.toml", table)]);
+        assert_eq!(
+            errors,
+            [error(
+                root.join("raw_tables/customer.toml"),
+                "table.hidden_columns[1]",
+                "column `nickname` does not exist on table `customer`"
+            )]
+        );
+    }
+
+    #[test]
+    fn unknown_table() {
+        let (root, errors) = validate("unknown_table", &[("raw_tables/vendor.toml", "")]);
+        assert_eq!(
+            errors,
+            [error(
+                root.join("raw_tables/vendor.toml"),
+                "",
+                "table `vendor` does not exist"
+            )]
+        );
+    }
+
+    #[test]
+    fn unknown_column() {
+        let (root, errors) = validate(
+            "unknown_column",
+            &[(
+                "raw_tables/customer.toml",
+                "[table]\ncolumns = [{ field = \"first_name\" }, { field = \"nickname\" }]\n",
+            )],
+        );
+        assert_eq!(
+            errors,
+            [error(
+                root.join("raw_tables/customer.toml"),
+                "table.columns[1].field",
+                "column `nickname` does not exist on table `customer`"
+            )]
+        );
+    }
+
+    #[test]
+    fn bad_relation() {
+        let (root, errors) = validate(
+            "bad_relation",
+            &[(
+                "composite_tables/customer.toml",
+                "[parent]\nname = \"customer\"\n\n[[children]]\nrelation = \"orders\"\n",
+            )],
+        );
+        assert_eq!(
+            errors,
+            [error(
+                root.join("composite_tables/customer.toml"),
+                "children[0].relation",
+                "relation `orders` does not exist on table `customer`"
+            )]
+        );
+    }
+
+    #[test]
+    fn bad_menu_links() {
+        let config = format!(
+            r#"{SITE}
+[[site.menu.items]]
+raw_table = "vendor"
+
+[[site.menu.items]]
+title = "Docs"
+link = "https://www.sea-ql.org/"
+dashboard = "default"
+
+[[site.menu.items]]
+children = [{{ raw_table = "customer" }}]
+"#
+        );
+        let (root, errors) = validate(
+            "bad_menu_links",
+            &[("config.toml", &config), ("raw_tables/customer.toml", "")],
+        );
+        let file = root.join("config.toml");
+        assert_eq!(
+            errors,
+            [
+                error(
+                    file.clone(),
+                    "site.menu.items[0].raw_table",
+                    "raw table `vendor` does not exist"
+                ),
+                error(
+                    file.clone(),
+                    "site.menu.items[1]",
+                    "menu entry needs exactly one of `dashboard`, `raw_table`, `composite_table`, `link` or `children`"
+                ),
+                error(file, "site.menu.items[2].title", "title is required"),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_of_a_bundle_are_located_in_the_bundle() {
+        let bundle = format!(
+            r#"{SITE}
+[[site.menu.items]]
+composite_table = "customer"
+
+[raw_tables.customer.table]
+columns = [{{ field = "nickname" }}]
+"#
+        );
+        let root = config_dir("bundle", &[("admin.toml", &bundle)]);
+        let path = root.join("admin.toml");
+        let config = ConfigParser::new().load_bundle(&path).unwrap();
+        let errors = config.validate(&entities()).unwrap_err().0;
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            errors,
+            [
+                error(
+                    path.clone(),
+                    "raw_tables.customer.table.columns[0].field",
+                    "column `nickname` does not exist on table `customer`"
+                ),
+                error(
+                    path,
+                    "site.menu.items[0].composite_table",
+                    "composite table `customer` does not exist"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,839 @@
+use chrono::{Datelike, Days, Duration, Months, NaiveDate, NaiveTime, Timelike, Weekday};
+use sea_orm::{
+    prelude::DateTime,
+    sea_query::{
+        Alias, Asterisk, Condition, Expr, Func, IntoColumnRef, IntoIden, JoinType, Query,
+        SelectStatement, SimpleExpr,
+    },
+    ConnectionTrait, DatabaseBackend, DbConn, DbErr, Order as SqlOrder, QueryResult,
+};
+use sea_orm_pro::{
+    Aggregate, ChartField, ChartQueryCfg, ColumnExpr, ColumnKind, DashboardChart, DrillDownPoint,
+    EntityMeta, FilterConditionCfg, Order, TimeBucket,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use super::virtual_column;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DashboardBody {
+    /// Dashboard the chart is on, the default dashboard if not given
+    #[serde(default)]
+    pub dashboard: Option<String>,
+    pub graph: String,
+    pub from: Option<DateTime>,
+    pub to: Option<DateTime>,
+    /// Time scale picked on the chart, the time scale of the chart config if not given
+    #[serde(default)]
+    pub timescale: Option<TimeBucket>,
+    /// Period to compare the data with
+    #[serde(default)]
+    pub compare: Option<Comparison>,
+}
+
+/// Period compared with the dates of the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// Period of the same length right before
+    PreviousPeriod,
+    /// Same period a year before
+    PreviousYear,
+}
+
+impl Comparison {
+    /// Start and end of the compared period, `None` if it's before the first day of the calendar
+    pub fn bounds(self, from: DateTime, to: DateTime) -> Option<(DateTime, DateTime)> {
+        match self {
+            Self::PreviousPeriod => {
+                let end = from.checked_sub_signed(Duration::microseconds(1))?;
+                Some((end.checked_sub_signed(to - from)?, end))
+            }
+            Self::PreviousYear => Some((
+                from.checked_sub_months(Months::new(12))?,
+                to.checked_sub_months(Months::new(12))?,
+            )),
+        }
+    }
+}
+
+/// Value of the chart at the key
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Datum {
+    pub key: String,
+    /// Series of the value, left out on charts of a single value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    pub val: f64,
+    /// Key of the bucket of the compared period in line with the key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_key: Option<String>,
+    /// Value of the compared period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_val: Option<f64>,
+    /// Rows behind the value, on charts with a drill-down
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drill_down: Option<DrillDown>,
+}
+
+impl Datum {
+    pub fn new(key: impl Into<String>, val: f64) -> Self {
+        Self {
+            key: key.into(),
+            series: None,
+            val,
+            compare_key: None,
+            compare_val: None,
+            drill_down: None,
+        }
+    }
+}
+
+/// Raw table filtered to the rows behind a value, the filters as taken by the table endpoints
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DrillDown {
+    pub table: String,
+    pub filters: Vec<FilterConditionCfg>,
+}
+
+/// Data of the chart compared with another period
+#[derive(Debug, Serialize)]
+pub struct ComparedData {
+    pub data: Vec<Datum>,
+    /// Totals of each series in both periods
+    pub totals: Vec<Total>,
+}
+
+/// Total of a series in the period of the chart and in the compared period
+#[derive(Debug, Serialize)]
+pub struct Total {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    pub val: Option<f64>,
+    pub compare_val: Option<f64>,
+    /// Change from the compared value in percent, none if the compared value is zero
+    pub change: Option<f64>,
+}
+
+const KEY_COLUMN: &str = "key";
+
+/// Most buckets filled in between the start and the end of a chart, e.g. a year of hours
+const MAX_BUCKETS: usize = 10_000;
+
+/// Column of the i-th value of the chart
+fn value_column(i: usize) -> String {
+    format!("val{i}")
+}
+
+/// Bucket of the dates of the key, the time scale picked on the chart, the bucket of the key
+/// or the time scale of the chart in this order. Keys of other columns aren't bucketed.
+pub fn key_bucket(
+    entities: &[EntityMeta],
+    query: &ChartQueryCfg,
+    picked: Option<TimeBucket>,
+    timescale: Option<TimeBucket>,
+) -> Option<TimeBucket> {
+    let key = query.key.as_ref()?;
+    let (_, column) = query.resolve_column(entities, &key.column).ok()?;
+    if !matches!(column.kind, ColumnKind::Date | ColumnKind::DateTime) {
+        return None;
+    }
+    picked.or(key.bucket).or(timescale)
+}
+
+/// Statement selecting the data of the chart query, dates of the key are grouped by the bucket
+/// and values of the date column are limited to `from` and `to`, date ranges of the filters
+/// are relative to `today`
+pub fn select(
+    db: &DbConn,
+    entities: &[EntityMeta],
+    query: &ChartQueryCfg,
+    bucket: Option<TimeBucket>,
+    from: Option<DateTime>,
+    to: Option<DateTime>,
+    today: NaiveDate,
+) -> Result<SelectStatement, String> {
+    let Some(entity) = entities
+        .iter()
+        .find(|entity| entity.table_name == query.table)
+    else {
+        return Err(format!("table `{}` does not exist", query.table));
+    };
+    let table = Alias::new(&query.table);
+    let column = |field: &str| -> Result<(Alias, Alias), String> {
+        let (entity, column) = query.resolve_column(entities, field)?;
+        Ok((Alias::new(&entity.table_name), Alias::new(&column.name)))
+    };
+
+    let mut stmt = Query::select();
+    stmt.from(table.clone());
+    for relation in query.join_relations(entity)? {
+        let to_table = Alias::new(&relation.to_table);
+        let on = relation
+            .from_columns
+            .iter()
+            .zip(relation.to_columns.iter())
+            .fold(Condition::all(), |cond, (from, to)| {
+                cond.add(
+                    Expr::col((table.clone(), Alias::new(from)))
+                        .equals((to_table.clone(), Alias::new(to))),
+                )
+            });
+        stmt.join(JoinType::LeftJoin, to_table, on);
+    }
+
+    if let Some(key) = &query.key {
+        let column = column(&key.column)?;
+        let expr: SimpleExpr = match bucket {
+            Some(bucket) => time_bucket(db, column, bucket),
+            // Keys are sent as text whatever the type of the column
+            None => Func::cast_as(Expr::col(column), text_keyword(db)).into(),
+        };
+        stmt.expr_as(expr, Alias::new(KEY_COLUMN))
+            .group_by_col(Alias::new(KEY_COLUMN));
+    }
+    for (i, (_, value)) in query.values().into_iter().enumerate() {
+        let expr: SimpleExpr = match (&value.column, &value.expr) {
+            (Some(field), _) => Expr::col(column(field)?).into(),
+            (None, Some(expr)) => {
+                let expr =
+                    ColumnExpr::parse(expr).map_err(|err| format!("value expression: {err}"))?;
+                virtual_column::column_expr(&expr, &query.table, db.get_database_backend())
+            }
+            (None, None) => Expr::col(Asterisk).into(),
+        };
+        let expr = match value.aggregate {
+            Aggregate::Count => Func::count(expr),
+            Aggregate::Sum => Func::sum(expr),
+            Aggregate::Avg => Func::avg(expr),
+            Aggregate::Min => Func::min(expr),
+            Aggregate::Max => Func::max(expr),
+        };
+        // Decimals such as prices are sent as floats, cents included
+        stmt.expr_as(
+            Func::cast_as(expr, float_keyword(db)),
+            Alias::new(value_column(i)),
+        );
+    }
+
+    let mut cond = Condition::all();
+    for filter in query.filters.iter() {
+        let expr = Expr::col(column(&filter.field)?).into();
+        cond = cond.add(virtual_column::filter_condition(expr, filter, today)?);
+    }
+    if let Some(date_column) = &query.date_column {
+        let date_column = column(date_column)?;
+        if let Some(from) = from {
+            cond = cond.add(Expr::col(date_column.clone()).gte(from));
+        }
+        if let Some(to) = to {
+            cond = cond.add(Expr::col(date_column).lte(to));
+        }
+    }
+    stmt.cond_where(cond);
+
+    let (field, order) = match &query.order_by {
+        Some(order_by) => (order_by.field, &order_by.order),
+        None => (ChartField::Key, &Order::Asc),
+    };
+    let order = match order {
+        Order::Asc => SqlOrder::Asc,
+        Order::Desc => SqlOrder::Desc,
+    };
+    match field {
+        ChartField::Key if query.key.is_some() => {
+            stmt.order_by(Alias::new(KEY_COLUMN), order);
+        }
+        ChartField::Key => {}
+        // Sorted by the first series
+        ChartField::Value => {
+            stmt.order_by(Alias::new(value_column(0)), order);
+        }
+    }
+    if let Some(limit) = query.limit {
+        stmt.limit(limit);
+    }
+    Ok(stmt)
+}
+
+/// Data of the chart from the rows selected by the statement of `select`,
+/// one datum per key and series, groups without a value are left out.
+///
+/// Buckets of dates between `from` and `to` without rows are filled in, unless the groups are
+/// sorted by value or limited, counts and sums are zero there while other aggregates have no value.
+pub fn data(
+    query: &ChartQueryCfg,
+    bucket: Option<TimeBucket>,
+    rows: &[QueryResult],
+    from: Option<DateTime>,
+    to: Option<DateTime>,
+) -> Result<Vec<Datum>, DbErr> {
+    let values = query.values();
+    let mut groups = Vec::new();
+    for row in rows {
+        let key: Option<String> = match query.key {
+            Some(_) => row.try_get("", KEY_COLUMN)?,
+            None => None,
+        };
+        let vals = (0..values.len())
+            .map(|i| row.try_get::<Option<f64>>("", &value_column(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        groups.push((key.unwrap_or_default(), vals));
+    }
+
+    let (field, order) = match &query.order_by {
+        Some(order_by) => (order_by.field, &order_by.order),
+        None => (ChartField::Key, &Order::Asc),
+    };
+    let keys = match (bucket, from, to) {
+        (Some(bucket), Some(from), Some(to))
+            if field == ChartField::Key && query.limit.is_none() =>
+        {
+            bucket_keys(bucket, from, to)
+        }
+        _ => None,
+    };
+    if let Some(keys) = keys {
+        let empty: Vec<_> = values
+            .iter()
+            .map(|(_, value)| match value.aggregate {
+                Aggregate::Count | Aggregate::Sum => Some(0.0),
+                Aggregate::Avg | Aggregate::Min | Aggregate::Max => None,
+            })
+            .collect();
+        // Keys of the buckets sort in the order of time
+        let mut filled: BTreeMap<_, _> = groups.into_iter().collect();
+        for key in keys {
+            filled.entry(key).or_insert_with(|| empty.clone());
+        }
+        groups = filled.into_iter().collect();
+        if matches!(order, Order::Desc) {
+            groups.reverse();
+        }
+    }
+
+    let mut data = Vec::new();
+    for (key, vals) in groups {
+        for ((series, _), val) in values.iter().zip(vals) {
+            if let Some(val) = val {
+                data.push(Datum {
+                    series: series.map(ToOwned::to_owned),
+                    ..Datum::new(key.clone(), val)
+                });
+            }
+        }
+    }
+
+    if let Some(key) = query.key.as_ref().filter(|key| !key.categories.is_empty()) {
+        // Listed keys come first, the others keep the order of the query
+        let position = |datum: &Datum| {
+            key.categories
+                .iter()
+                .position(|category| *category == datum.key)
+                .unwrap_or(key.categories.len())
+        };
+        data.sort_by_key(position);
+    }
+    Ok(data)
+}
+
+/// Query of the totals of the chart, the values of all rows in the dates of the chart
+pub fn totals_query(query: &ChartQueryCfg) -> ChartQueryCfg {
+    ChartQueryCfg {
+        key: None,
+        order_by: None,
+        limit: None,
+        ..query.clone()
+    }
+}
+
+/// Sum of the values of each series, as totals of charts without a query
+pub fn sum_series(data: &[Datum]) -> Vec<Datum> {
+    let mut sums: Vec<Datum> = Vec::new();
+    for datum in data {
+        match sums.iter_mut().find(|sum| sum.series == datum.series) {
+            Some(sum) => sum.val += datum.val,
+            None => sums.push(Datum {
+                series: datum.series.clone(),
+                ..Datum::new("", datum.val)
+            }),
+        }
+    }
+    sums
+}
+
+/// Data with the values of the compared period, in line by the position of the bucket
+/// if the keys are buckets of dates or by the key otherwise
+pub fn compare(mut data: Vec<Datum>, compared: &[Datum], bucketed: bool) -> Vec<Datum> {
+    let keys = |data: &[Datum]| {
+        let mut keys: Vec<String> = Vec::new();
+        for datum in data {
+            if !keys.contains(&datum.key) {
+                keys.push(datum.key.clone());
+            }
+        }
+        keys
+    };
+    let buckets: HashMap<_, _> = if bucketed {
+        keys(&data).into_iter().zip(keys(compared)).collect()
+    } else {
+        HashMap::new()
+    };
+    let values: HashMap<_, _> = compared
+        .iter()
+        .map(|datum| ((datum.key.as_str(), datum.series.as_deref()), datum.val))
+        .collect();
+    for datum in data.iter_mut() {
+        let key = if bucketed {
+            let Some(key) = buckets.get(&datum.key) else {
+                continue;
+            };
+            datum.compare_key = Some(key.clone());
+            key
+        } else {
+            &datum.key
+        };
+        datum.compare_val = values
+            .get(&(key.as_str(), datum.series.as_deref()))
+            .copied();
+    }
+    data
+}
+
+/// Totals of each series in the period of the chart and in the compared period
+pub fn totals(totals: &[Datum], compared: &[Datum]) -> Vec<Total> {
+    let mut series: Vec<Option<&str>> = Vec::new();
+    for datum in totals.iter().chain(compared) {
+        if !series.contains(&datum.series.as_deref()) {
+            series.push(datum.series.as_deref());
+        }
+    }
+    let value = |data: &[Datum], series: Option<&str>| {
+        data.iter()
+            .find(|datum| datum.series.as_deref() == series)
+            .map(|datum| datum.val)
+    };
+    series
+        .into_iter()
+        .map(|series| {
+            let val = value(totals, series);
+            let compare_val = value(compared, series);
+            let change = match (val, compare_val) {
+                (Some(val), Some(compare_val)) if compare_val != 0.0 => {
+                    Some((val - compare_val) / compare_val.abs() * 100.0)
+                }
+                _ => None,
+            };
+            Total {
+                series: series.map(ToOwned::to_owned),
+                val,
+                compare_val,
+                change,
+            }
+        })
+        .collect()
+}
+
+/// Drill-down of each value of the chart, by the bucket of dates of the key if it's bucketed.
+/// Values without a value for the filters, e.g. a key that isn't a date, are left without.
+pub fn drill_down(
+    entities: &[EntityMeta],
+    chart: &DashboardChart,
+    bucket: Option<TimeBucket>,
+    data: &mut [Datum],
+) {
+    let Some(cfg) = &chart.drill_down else {
+        return;
+    };
+    let Some(entity) = entities
+        .iter()
+        .find(|entity| entity.table_name == cfg.table)
+    else {
+        return;
+    };
+    let templates = cfg.templates(entities, chart.query.as_ref());
+    if templates.is_empty() {
+        return;
+    }
+    let format = |date: DateTime| date.format("%Y-%m-%d %H:%M:%S").to_string();
+    for datum in data.iter_mut() {
+        let start = bucket.and_then(|bucket| parse_bucket_key(bucket, &datum.key));
+        let end = bucket
+            .zip(start)
+            .and_then(|(bucket, start)| next_bucket(bucket, start));
+        let (start, end) = (start.map(format), end.map(format));
+        let point = DrillDownPoint {
+            key: &datum.key,
+            series: datum.series.as_deref(),
+            start: start.as_deref(),
+            end: end.as_deref(),
+        };
+        datum.drill_down = templates
+            .iter()
+            .map(|template| template.fill(entity, &point))
+            .collect::<Option<_>>()
+            .map(|filters| DrillDown {
+                table: cfg.table.clone(),
+                filters,
+            });
+    }
+}
+
+/// Key of the bucket of dates the column falls in, written as `2024-12-22 10:00`, `2024-12-22`,
+/// `2024-W51`, `2024-12`, `2024-Q4` or `2024`
+pub fn time_bucket(db: &DbConn, col: impl IntoColumnRef, bucket: TimeBucket) -> SimpleExpr {
+    let col = col.into_column_ref();
+    let format = match (db.get_database_backend(), bucket) {
+        (_, TimeBucket::Day) => return cast_as_day(db, col),
+        (_, TimeBucket::Month) => return cast_as_year_month(db, col),
+        (DatabaseBackend::MySql, TimeBucket::Quarter) => {
+            return Func::cust(Alias::new("CONCAT"))
+                .arg(Func::cust(Alias::new("YEAR")).arg(Expr::col(col.clone())))
+                .arg("-Q")
+                .arg(Func::cust(Alias::new("QUARTER")).arg(Expr::col(col)))
+                .into();
+        }
+        (DatabaseBackend::Sqlite, TimeBucket::Quarter) => {
+            let part = |format: &str| {
+                Func::cust(Alias::new("STRFTIME"))
+                    .arg(format)
+                    .arg(Expr::col(col.clone()))
+            };
+            return Expr::cust_with_exprs(
+                "? || '-Q' || ((? + 2) / 3)",
+                [
+                    part("%Y").into(),
+                    Func::cast_as(part("%m"), int_keyword(db)).into(),
+                ],
+            );
+        }
+        (DatabaseBackend::MySql, bucket) => match bucket {
+            TimeBucket::Hour => "%Y-%m-%d %H:00",
+            TimeBucket::Week => "%x-W%v",
+            _ => "%Y",
+        },
+        (DatabaseBackend::Postgres, bucket) => match bucket {
+            TimeBucket::Hour => "YYYY-MM-DD HH24:00",
+            TimeBucket::Week => "IYYY-\"W\"IW",
+            TimeBucket::Quarter => "YYYY-\"Q\"Q",
+            _ => "YYYY",
+        },
+        (DatabaseBackend::Sqlite, bucket) => match bucket {
+            TimeBucket::Hour => "%Y-%m-%d %H:00",
+            TimeBucket::Week => "%G-W%V",
+            _ => "%Y",
+        },
+    };
+    date_format(db, col, format)
+}
+
+/// Month of the column, written as `2024-12`
+pub fn cast_as_year_month(db: &DbConn, col: impl IntoColumnRef) -> SimpleExpr {
+    let format = match db.get_database_backend() {
+        DatabaseBackend::MySql => "%Y-%m",
+        DatabaseBackend::Postgres => "YYYY-mm",
+        DatabaseBackend::Sqlite => "%Y-%m",
+    };
+    date_format(db, col, format)
+}
+
+/// Day of the column, written as `2024-12-22`
+pub fn cast_as_day(db: &DbConn, col: impl IntoColumnRef) -> SimpleExpr {
+    let format = match db.get_database_backend() {
+        DatabaseBackend::MySql => "%Y-%m-%d",
+        DatabaseBackend::Postgres => "YYYY-mm-dd",
+        DatabaseBackend::Sqlite => "%Y-%m-%d",
+    };
+    date_format(db, col, format)
+}
+
+/// Date of the column written in the format of the database backend
+fn date_format(db: &DbConn, col: impl IntoColumnRef, format: &str) -> SimpleExpr {
+    let func = match db.get_database_backend() {
+        DatabaseBackend::MySql => Func::cust(Alias::new("DATE_FORMAT"))
+            .arg(Expr::col(col.into_column_ref()))
+            .arg(format),
+        DatabaseBackend::Postgres => Func::cust(Alias::new("TO_CHAR"))
+            .arg(Expr::col(col.into_column_ref()))
+            .arg(format),
+        DatabaseBackend::Sqlite => Func::cust(Alias::new("STRFTIME"))
+            .arg(format)
+            .arg(Expr::col(col.into_column_ref())),
+    };
+    func.into()
+}
+
+/// Start of the bucket the date falls in
+fn bucket_start(bucket: TimeBucket, date: DateTime) -> DateTime {
+    let day = date.date();
+    let first = |month: u32| {
+        NaiveDate::from_ymd_opt(day.year(), month, 1)
+            .expect("first day of the month")
+            .and_time(NaiveTime::MIN)
+    };
+    match bucket {
+        TimeBucket::Hour => day.and_time(NaiveTime::MIN) + Duration::hours(date.hour().into()),
+        TimeBucket::Day => day.and_time(NaiveTime::MIN),
+        TimeBucket::Week => {
+            let monday = day
+                .checked_sub_days(Days::new(day.weekday().num_days_from_monday().into()))
+                .unwrap_or(NaiveDate::MIN);
+            monday.and_time(NaiveTime::MIN)
+        }
+        TimeBucket::Month => first(day.month()),
+        TimeBucket::Quarter => first(day.month0() / 3 * 3 + 1),
+        TimeBucket::Year => first(1),
+    }
+}
+
+/// Start of the bucket after the one starting at `start`, `None` past the end of the calendar
+fn next_bucket(bucket: TimeBucket, start: DateTime) -> Option<DateTime> {
+    match bucket {
+        TimeBucket::Hour => start.checked_add_signed(Duration::hours(1)),
+        TimeBucket::Day => start.checked_add_signed(Duration::days(1)),
+        TimeBucket::Week => start.checked_add_signed(Duration::weeks(1)),
+        TimeBucket::Month => start.checked_add_months(Months::new(1)),
+        TimeBucket::Quarter => start.checked_add_months(Months::new(3)),
+        TimeBucket::Year => start.checked_add_months(Months::new(12)),
+    }
+}
+
+/// Key of the bucket starting at `start`, as written by [`time_bucket`]
+fn bucket_key(bucket: TimeBucket, start: DateTime) -> String {
+    match bucket {
+        TimeBucket::Hour => start.format("%Y-%m-%d %H:00").to_string(),
+        TimeBucket::Day => start.format("%Y-%m-%d").to_string(),
+        TimeBucket::Week => start.format("%G-W%V").to_string(),
+        TimeBucket::Month => start.format("%Y-%m").to_string(),
+        TimeBucket::Quarter => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+        TimeBucket::Year => start.format("%Y").to_string(),
+    }
+}
+
+/// Start of the bucket of the key written by [`bucket_key`], `None` if it's not a key of the bucket
+fn parse_bucket_key(bucket: TimeBucket, key: &str) -> Option<DateTime> {
+    let date = |key: &str| NaiveDate::parse_from_str(key, "%Y-%m-%d").ok();
+    let start = match bucket {
+        TimeBucket::Hour => DateTime::parse_from_str(key, "%Y-%m-%d %H:%M").ok()?,
+        TimeBucket::Day => date(key)?.and_time(NaiveTime::MIN),
+        TimeBucket::Week => {
+            let (year, week) = key.split_once("-W")?;
+            NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)?
+                .and_time(NaiveTime::MIN)
+        }
+        TimeBucket::Month => date(&format!("{key}-01"))?.and_time(NaiveTime::MIN),
+        TimeBucket::Quarter => {
+            let (year, quarter) = key.split_once("-Q")?;
+            let month = quarter.parse::<u32>().ok()?.checked_sub(1)? * 3 + 1;
+            date(&format!("{year}-{month:02}-01"))?.and_time(NaiveTime::MIN)
+        }
+        TimeBucket::Year => date(&format!("{key}-01-01"))?.and_time(NaiveTime::MIN),
+    };
+    // Keys of the bucket only, e.g. not `2024-12-22 10:30` by hour
+    (bucket_key(bucket, start) == key).then_some(start)
+}
+
+/// Keys of the buckets between `from` and `to`, `None` if there are too many to fill in
+fn bucket_keys(bucket: TimeBucket, from: DateTime, to: DateTime) -> Option<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut start = bucket_start(bucket, from);
+    while start <= to {
+        if keys.len() == MAX_BUCKETS {
+            return None;
+        }
+        keys.push(bucket_key(bucket, start));
+        match next_bucket(bucket, start) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    Some(keys)
+}
+
+pub fn float_keyword(db: &DbConn) -> impl IntoIden {
+    match db.get_database_backend() {
+        DatabaseBackend::MySql => Alias::new("DOUBLE"),
+        DatabaseBackend::Postgres => Alias::new("FLOAT8"),
+        DatabaseBackend::Sqlite => Alias::new("REAL"),
+    }
+}
+
+pub fn int_keyword(db: &DbConn) -> impl IntoIden {
+    match db.get_database_backend() {
+        DatabaseBackend::MySql => Alias::new("SIGNED INTEGER"),
+        DatabaseBackend::Postgres => Alias::new("INT4"),
+        DatabaseBackend::Sqlite => Alias::new("INT"),
+    }
+}
+
+pub fn text_keyword(db: &DbConn) -> impl IntoIden {
+    match db.get_database_backend() {
+        DatabaseBackend::MySql => Alias::new("CHAR"),
+        DatabaseBackend::Postgres | DatabaseBackend::Sqlite => Alias::new("TEXT"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> DateTime {
+        at(y, m, d, 0, 0)
+    }
+
+    #[test]
+    fn start_of_the_bucket() {
+        let date = at(2024, 11, 30, 10, 45);
+        assert_eq!(
+            bucket_start(TimeBucket::Hour, date),
+            at(2024, 11, 30, 10, 0)
+        );
+        assert_eq!(bucket_start(TimeBucket::Day, date), day(2024, 11, 30));
+        assert_eq!(bucket_start(TimeBucket::Week, date), day(2024, 11, 25));
+        assert_eq!(bucket_start(TimeBucket::Month, date), day(2024, 11, 1));
+        assert_eq!(bucket_start(TimeBucket::Quarter, date), day(2024, 10, 1));
+        assert_eq!(bucket_start(TimeBucket::Year, date), day(2024, 1, 1));
+    }
+
+    #[test]
+    fn iso_weeks_across_the_new_year() {
+        // December 31 2024 is a Tuesday in the first week of 2025
+        let start = bucket_start(TimeBucket::Week, day(2024, 12, 31));
+        assert_eq!(start, day(2024, 12, 30));
+        assert_eq!(bucket_key(TimeBucket::Week, start), "2025-W01");
+        // January 1 2021 is a Friday in the last week of 2020
+        let start = bucket_start(TimeBucket::Week, day(2021, 1, 1));
+        assert_eq!(start, day(2020, 12, 28));
+        assert_eq!(bucket_key(TimeBucket::Week, start), "2020-W53");
+        // A Sunday is in the week of the Monday before
+        assert_eq!(
+            bucket_start(TimeBucket::Week, at(2024, 12, 29, 23, 59)),
+            day(2024, 12, 23)
+        );
+    }
+
+    #[test]
+    fn next_bucket_of_months_keeps_the_first_day() {
+        assert_eq!(
+            next_bucket(TimeBucket::Month, day(2024, 1, 1)),
+            Some(day(2024, 2, 1))
+        );
+        assert_eq!(
+            next_bucket(TimeBucket::Month, day(2024, 12, 1)),
+            Some(day(2025, 1, 1))
+        );
+        assert_eq!(
+            next_bucket(TimeBucket::Quarter, day(2024, 10, 1)),
+            Some(day(2025, 1, 1))
+        );
+        assert_eq!(
+            next_bucket(TimeBucket::Week, day(2024, 12, 30)),
+            Some(day(2025, 1, 6))
+        );
+    }
+
+    #[test]
+    fn buckets_at_the_ends_of_the_calendar() {
+        let last_year = bucket_start(TimeBucket::Year, NaiveDate::MAX.and_time(NaiveTime::MIN));
+        assert_eq!(next_bucket(TimeBucket::Year, last_year), None);
+        assert_eq!(
+            bucket_keys(TimeBucket::Year, last_year, DateTime::MAX),
+            Some(vec![bucket_key(TimeBucket::Year, last_year)])
+        );
+        let first_day = NaiveDate::MIN.and_time(NaiveTime::MIN);
+        assert!(bucket_start(TimeBucket::Week, first_day) <= first_day);
+    }
+
+    #[test]
+    fn compared_periods_before_the_calendar() {
+        let first_day = NaiveDate::MIN.and_time(NaiveTime::MIN);
+        assert_eq!(
+            Comparison::PreviousYear.bounds(first_day, day(2024, 1, 1)),
+            None
+        );
+        assert_eq!(
+            Comparison::PreviousPeriod.bounds(first_day, day(2024, 1, 1)),
+            None
+        );
+        assert_eq!(
+            Comparison::PreviousYear.bounds(day(2024, 2, 29), day(2024, 3, 31)),
+            Some((day(2023, 2, 28), day(2023, 3, 31)))
+        );
+    }
+
+    #[test]
+    fn keys_are_parsed_back_to_the_start() {
+        let keys = [
+            (
+                TimeBucket::Hour,
+                "2024-12-22 10:00",
+                at(2024, 12, 22, 10, 0),
+            ),
+            (TimeBucket::Day, "2024-02-29", day(2024, 2, 29)),
+            (TimeBucket::Week, "2025-W01", day(2024, 12, 30)),
+            (TimeBucket::Week, "2020-W53", day(2020, 12, 28)),
+            (TimeBucket::Month, "2024-02", day(2024, 2, 1)),
+            (TimeBucket::Quarter, "2024-Q4", day(2024, 10, 1)),
+            (TimeBucket::Year, "2024", day(2024, 1, 1)),
+        ];
+        for (bucket, key, start) in keys {
+            assert_eq!(parse_bucket_key(bucket, key), Some(start), "{key}");
+            assert_eq!(bucket_key(bucket, start), key);
+        }
+    }
+
+    #[test]
+    fn keys_of_another_bucket_are_not_parsed() {
+        let keys = [
+            (TimeBucket::Hour, "2024-12-22 10:30"),
+            (TimeBucket::Day, "2024-02-30"),
+            (TimeBucket::Week, "2024-W53"),
+            (TimeBucket::Week, "2024-W1"),
+            (TimeBucket::Month, "2024-13"),
+            (TimeBucket::Month, "2024-2"),
+            (TimeBucket::Quarter, "2024-Q0"),
+            (TimeBucket::Quarter, "2024-Q5"),
+            (TimeBucket::Year, "24"),
+            (TimeBucket::Day, "2024-12"),
+        ];
+        for (bucket, key) in keys {
+            assert_eq!(parse_bucket_key(bucket, key), None, "{key}");
+        }
+    }
+
+    #[test]
+    fn months_are_filled_in() {
+        let keys = bucket_keys(TimeBucket::Month, day(2024, 11, 30), day(2025, 2, 1));
+        assert_eq!(keys.unwrap(), ["2024-11", "2024-12", "2025-01", "2025-02"]);
+        // From the end of January, February is not skipped
+        let keys = bucket_keys(TimeBucket::Month, at(2024, 1, 31, 12, 0), day(2024, 3, 31));
+        assert_eq!(keys.unwrap(), ["2024-01", "2024-02", "2024-03"]);
+    }
+
+    #[test]
+    fn weeks_are_filled_in_across_the_new_year() {
+        let keys = bucket_keys(TimeBucket::Week, day(2020, 12, 20), day(2021, 1, 10));
+        assert_eq!(
+            keys.unwrap(),
+            ["2020-W51", "2020-W52", "2020-W53", "2021-W01"]
+        );
+    }
+
+    #[test]
+    fn too_many_buckets_are_not_filled_in() {
+        assert!(bucket_keys(TimeBucket::Hour, day(2020, 1, 1), day(2024, 1, 1)).is_none());
+        assert!(bucket_keys(TimeBucket::Day, day(2020, 1, 1), day(2024, 1, 1)).is_some());
+        assert_eq!(
+            bucket_keys(TimeBucket::Day, day(2024, 1, 2), day(2024, 1, 1)),
+            Some(Vec::new())
+        );
+    }
+}
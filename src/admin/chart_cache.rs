@@ -0,0 +1,114 @@
+use loco_rs::cache::Cache;
+use sea_orm_pro::{DashboardChart, EntityMeta, DEFAULT_DASHBOARD};
+use serde_json::Value;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{chart::DashboardBody, chart_provider::ChartRegistry};
+
+/// Tables the chart is computed from, the table and the joined tables of the query
+/// or the tables declared by the chart provider
+pub fn chart_tables(
+    entities: &[EntityMeta],
+    registry: &ChartRegistry,
+    chart: &DashboardChart,
+) -> Vec<String> {
+    let Some(query) = &chart.query else {
+        return registry
+            .get(&chart.chart)
+            .map(|provider| provider.tables().iter().map(|t| t.to_string()).collect())
+            .unwrap_or_default();
+    };
+    let joined = entities
+        .iter()
+        .find(|entity| entity.table_name == query.table)
+        .and_then(|entity| query.join_relations(entity).ok())
+        .unwrap_or_default();
+    std::iter::once(query.table.clone())
+        .chain(joined.into_iter().map(|relation| relation.to_table.clone()))
+        .collect()
+}
+
+/// Key of the cached data from the chart config, the request and the generation of the tables,
+/// a change of a table starts a new generation and leaves the data of the previous one to expire
+pub async fn key(
+    cache: &Cache,
+    chart: &DashboardChart,
+    body: &DashboardBody,
+    tables: &[String],
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    // The chart config changes on reload
+    serde_json::to_string(chart)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    let request = (
+        body.dashboard.as_deref().unwrap_or(DEFAULT_DASHBOARD),
+        body.from,
+        body.to,
+        body.timescale,
+        body.compare,
+    );
+    serde_json::to_string(&request)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    for table in tables {
+        generation(cache, table).await.hash(&mut hasher);
+    }
+    format!("chart:{}:{:016x}", chart.chart, hasher.finish())
+}
+
+pub async fn get(cache: &Cache, key: &str) -> Option<Value> {
+    match cache.get(key).await {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!("cannot read chart data from the cache: {err}");
+            None
+        }
+    }
+}
+
+pub async fn insert(cache: &Cache, key: &str, value: &Value, ttl: Duration) {
+    if let Err(err) = cache.insert_with_expiry(key, value, ttl).await {
+        tracing::warn!("cannot write chart data to the cache: {err}");
+    }
+}
+
+/// Start a new generation of the tables, cached data of the charts computed from them is left behind
+pub async fn invalidate(cache: &Cache, tables: impl IntoIterator<Item = &str>) {
+    for table in tables {
+        if let Err(err) = cache
+            .insert(&generation_key(table), &new_generation())
+            .await
+        {
+            tracing::warn!("cannot drop cached chart data of `{table}`: {err}");
+        }
+    }
+}
+
+/// Generation of the table, a new one if it's not in the cache, e.g. evicted,
+/// generations are stored with no TTL as the cached data of a table is keyed by them
+async fn generation(cache: &Cache, table: &str) -> u64 {
+    let key = generation_key(table);
+    if let Ok(Some(generation)) = cache.get(&key).await {
+        return generation;
+    }
+    let generation = new_generation();
+    if let Err(err) = cache.insert(&key, &generation).await {
+        tracing::warn!("cannot write chart data generation of `{table}` to the cache: {err}");
+    }
+    generation
+}
+
+fn generation_key(table: &str) -> String {
+    format!("chart_generation:{table}")
+}
+
+fn new_generation() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use loco_rs::Result;
+use sea_orm::DbConn;
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::chart::{DashboardBody, Datum};
+
+/// Chart computed in Rust, for charts too complex for a query in the dashboard config
+#[async_trait]
+pub trait ChartProvider: Send + Sync {
+    /// Key of the chart, the `chart` of the dashboard config
+    fn name(&self) -> &str;
+
+    /// Tables the chart is computed from, cached data of the chart is dropped once they change
+    fn tables(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Data of the chart requested by the dashboard
+    async fn data(&self, db: &DbConn, body: &DashboardBody) -> Result<Vec<Datum>>;
+}
+
+/// Chart providers by name, shared with the dashboard endpoint
+#[derive(Default, Clone)]
+pub struct ChartRegistry {
+    providers: BTreeMap<String, Arc<dyn ChartProvider>>,
+}
+
+impl ChartRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the provider, replacing any provider of the same name
+    pub fn register(mut self, provider: impl ChartProvider + 'static) -> Self {
+        self.providers
+            .insert(provider.name().to_owned(), Arc::new(provider));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn ChartProvider>> {
+        self.providers.get(name)
+    }
+
+    /// Names of the registered charts, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.providers.keys().map(String::as_str)
+    }
+}
@@ -0,0 +1,188 @@
+use arc_swap::ArcSwapOption;
+use chrono::NaiveDate;
+use include_dir::{include_dir, Dir};
+use notify::{RecursiveMode, Watcher};
+use sea_orm_pro::{
+    ConfigError, ConfigParser, EntityMeta, FieldError, JsonCfg, ValidationError, ValidationErrors,
+    WriteMode,
+};
+use std::{
+    path::Path,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use super::{date_range, entities::entity_metas};
+
+/// Root folder of the admin panel config
+pub const CONFIG_ROOT: &str = "pro_admin";
+
+/// Admin panel config embedded in the binary at compile time, only the files read
+/// in production are copied to `OUT_DIR` by the build script
+pub static EMBEDDED_CONFIG: Dir<'static> = include_dir!("$OUT_DIR/pro_admin");
+
+/// Quiet period after a file change before reloading, editors tend to write a file in bursts
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Parsed and validated admin panel config shared by all requests
+pub struct ConfigStore {
+    root: String,
+    /// Environment selecting the overlay merged over the base config
+    environment: String,
+    /// Embedded config, with files in the root folder layered on top
+    embedded: Option<&'static Dir<'static>>,
+    /// Metadata of the registered entities, the config is validated against
+    entities: Vec<EntityMeta>,
+    /// Last good config
+    config: ArcSwapOption<JsonCfg>,
+    /// Error of the last reload, cleared once the config loads again
+    error: ArcSwapOption<ConfigError>,
+}
+
+impl ConfigStore {
+    /// Load the config in the root folder, the error is kept in the store if it fails
+    pub fn new<T: Into<String>, E: Into<String>>(root: T, environment: E) -> Self {
+        Self::load(root.into(), environment.into(), None)
+    }
+
+    /// Load the embedded config overridden by files in the root folder, if it exists
+    pub fn with_embedded<T: Into<String>, E: Into<String>>(
+        embedded: &'static Dir<'static>,
+        root: T,
+        environment: E,
+    ) -> Self {
+        Self::load(root.into(), environment.into(), Some(embedded))
+    }
+
+    fn load(root: String, environment: String, embedded: Option<&'static Dir<'static>>) -> Self {
+        let store = Self {
+            root,
+            environment,
+            embedded,
+            entities: entity_metas(),
+            config: ArcSwapOption::empty(),
+            error: ArcSwapOption::empty(),
+        };
+        let _ = store.reload();
+        store
+    }
+
+    /// The last good config, if any
+    pub fn config(&self) -> Option<Arc<JsonCfg>> {
+        self.config.load_full()
+    }
+
+    /// The error of the last reload, if it failed
+    pub fn error(&self) -> Option<Arc<ConfigError>> {
+        self.error.load_full()
+    }
+
+    /// Current date in the timezone of the site of the last good config
+    pub fn today(&self) -> NaiveDate {
+        let config = self.config();
+        let timezone = config
+            .as_ref()
+            .and_then(|config| config.site.timezone.as_deref());
+        date_range::today(timezone)
+    }
+
+    /// Metadata of the registered entities
+    pub fn entities(&self) -> &[EntityMeta] {
+        &self.entities
+    }
+
+    /// Check a row written to the table against the last good config
+    pub fn check_input(
+        &self,
+        table: &str,
+        row: &serde_json::Map<String, serde_json::Value>,
+        mode: WriteMode,
+    ) -> Vec<FieldError> {
+        match self.config() {
+            Some(config) => config.check_input(&self.entities, table, row, mode),
+            None => Vec::new(),
+        }
+    }
+
+    /// Fields of the table that must not be taken by another row
+    pub fn unique_fields(&self, table: &str) -> Vec<String> {
+        match self.config() {
+            Some(config) => config
+                .unique_fields(&self.entities, table)
+                .map(str::to_owned)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Parse and validate the config from the disk, the last good config is kept on failure
+    pub fn reload(&self) -> Result<Arc<JsonCfg>, Arc<ConfigError>> {
+        match self.load_config() {
+            Ok(config) => {
+                let config = Arc::new(config);
+                self.config.store(Some(config.clone()));
+                self.error.store(None);
+                Ok(config)
+            }
+            Err(err) => {
+                let err = Arc::new(err);
+                self.error.store(Some(err.clone()));
+                Err(err)
+            }
+        }
+    }
+
+    /// Load config, validate it against the registered entities and resolve the options
+    fn load_config(&self) -> Result<JsonCfg, ConfigError> {
+        let parser = ConfigParser::new().with_environment(&self.environment);
+        let mut config = match self.embedded {
+            Some(embedded) => parser.load_embedded(embedded, Some(&self.root))?,
+            None => parser.load_config(self.root.as_str())?,
+        };
+        let mut errors = match config.validate(&self.entities) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.0,
+        };
+        if let Some(timezone) = &config.site.timezone {
+            if let Err(message) = date_range::check_timezone(timezone) {
+                errors.push(ValidationError {
+                    file: config.sources.site_file(),
+                    path: "site.timezone".to_owned(),
+                    message,
+                });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(ValidationErrors(errors).into());
+        }
+        config.resolve_options(&self.entities);
+        Ok(config)
+    }
+
+    /// Reload the config whenever a file in the root folder changes
+    pub fn watch(self: &Arc<Self>) -> notify::Result<()> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if res.is_ok_and(|event| !event.kind.is_access()) {
+                    let _ = tx.send(());
+                }
+            })?;
+        watcher.watch(Path::new(&self.root), RecursiveMode::Recursive)?;
+
+        let store = self.clone();
+        thread::spawn(move || {
+            // The watcher stops once dropped, keep it alive along with the thread
+            let _watcher = watcher;
+            while rx.recv().is_ok() {
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                match store.reload() {
+                    Ok(_) => tracing::info!("admin panel config reloaded"),
+                    Err(err) => tracing::error!("admin panel config not reloaded: {err}"),
+                }
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use sea_orm::prelude::DateTime;
+use sea_orm_pro::{DashboardChart, DatePeriod, DateRange, DateUnit, JsonCfg};
+
+/// Current date in the timezone of the site, UTC if not given
+pub fn today(timezone: Option<&str>) -> NaiveDate {
+    date_at(Utc::now(), timezone)
+}
+
+/// Date of the instant in the timezone, UTC if not given or unknown,
+/// the timezone is checked once the config is loaded
+fn date_at(now: chrono::DateTime<Utc>, timezone: Option<&str>) -> NaiveDate {
+    match timezone.map(str::parse::<Tz>) {
+        Some(Ok(tz)) => now.with_timezone(&tz).date_naive(),
+        _ => now.date_naive(),
+    }
+}
+
+/// Error of the timezone name, e.g. `Asia/Shanghai`
+pub fn check_timezone(timezone: &str) -> Result<(), String> {
+    match timezone.parse::<Tz>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!(
+            "unknown timezone `{timezone}`, e.g. `UTC` or `Asia/Shanghai`"
+        )),
+    }
+}
+
+/// First and last day of the range as of `today`, ranges reaching beyond the calendar
+/// stop at its first day
+pub fn days(range: DateRange, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let day_before = |date: NaiveDate| date.pred_opt().unwrap_or(NaiveDate::MIN);
+    match range {
+        DateRange::Today => (today, today),
+        DateRange::Yesterday => (day_before(today), day_before(today)),
+        DateRange::Last { amount, unit } => {
+            let before = match unit {
+                DateUnit::Day => today.checked_sub_days(Days::new(amount.into())),
+                DateUnit::Week => today.checked_sub_days(Days::new(u64::from(amount) * 7)),
+                DateUnit::Month => today.checked_sub_months(Months::new(amount)),
+            };
+            let first = before
+                .and_then(|before| before.succ_opt())
+                .unwrap_or(NaiveDate::MIN);
+            (first, today)
+        }
+        DateRange::This(period) => period_days(period, today),
+        DateRange::Previous(period) => {
+            let (first, _) = period_days(period, today);
+            period_days(period, day_before(first))
+        }
+        DateRange::MonthToDate => (period_days(DatePeriod::Month, today).0, today),
+    }
+}
+
+/// First and last day of the period the date is in
+fn period_days(period: DatePeriod, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let (month, months) = match period {
+        DatePeriod::Month => (date.month(), 1),
+        DatePeriod::Quarter => (date.month0() / 3 * 3 + 1, 3),
+        DatePeriod::Year => (1, 12),
+    };
+    let first = NaiveDate::from_ymd_opt(date.year(), month, 1).expect("first day of the month");
+    let last = first
+        .checked_add_months(Months::new(months))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(NaiveDate::MAX);
+    (first, last)
+}
+
+/// Start of the first day and end of the last day
+pub fn bounds((first, last): (NaiveDate, NaiveDate)) -> (DateTime, DateTime) {
+    (first.and_time(NaiveTime::MIN), last.and_time(end_of_day()))
+}
+
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_micro_opt(23, 59, 59, 999_999).expect("last microsecond of the day")
+}
+
+/// Fill in the default dates of the date pickers from the default date range of the charts
+pub fn resolve_default_dates(config: &mut JsonCfg, today: NaiveDate) {
+    let dashboards = std::iter::once(&mut config.dashboard).chain(config.dashboards.values_mut());
+    for chart in dashboards.flat_map(|dashboard| dashboard.charts_mut()) {
+        let Some(Ok(range)) = chart.default_date_range() else {
+            continue;
+        };
+        let (first, last) = days(range, today);
+        // Written as the date picker of the time scale does
+        let format = match chart.timescale() {
+            Some("month") => "%Y-%m",
+            Some("year") => "%Y",
+            _ => "%Y-%m-%d",
+        };
+        chart.set_default_dates(
+            first.format(format).to_string(),
+            last.format(format).to_string(),
+        );
+    }
+}
+
+/// Bounds of the chart data if the request leaves them out, from the default date range
+/// or the default dates of the date picker
+pub fn default_bounds(
+    chart: &DashboardChart,
+    today: NaiveDate,
+) -> (Option<DateTime>, Option<DateTime>) {
+    if let Some(Ok(range)) = chart.default_date_range() {
+        let (from, to) = bounds(days(range, today));
+        return (Some(from), Some(to));
+    }
+    let (from, to) = chart.default_dates();
+    (
+        from.and_then(picker_days)
+            .map(|(first, _)| first.and_time(NaiveTime::MIN)),
+        to.and_then(picker_days)
+            .map(|(_, last)| last.and_time(end_of_day())),
+    )
+}
+
+/// Days of the date written in the date picker, the whole month or year if the day is left out
+fn picker_days(date: &str) -> Option<(NaiveDate, NaiveDate)> {
+    if let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Some((day, day));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(&format!("{date}-01"), "%Y-%m-%d") {
+        return Some(period_days(DatePeriod::Month, day));
+    }
+    let day = NaiveDate::from_ymd_opt(date.parse().ok()?, 1, 1)?;
+    Some(period_days(DatePeriod::Year, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn days_of(range: &str, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        days(range.parse().unwrap(), today)
+    }
+
+    #[test]
+    fn last_days_and_weeks_end_today() {
+        let today = date(2024, 3, 3);
+        assert_eq!(days_of("last 1 day", today), (today, today));
+        assert_eq!(days_of("last 7 days", today), (date(2024, 2, 26), today));
+        assert_eq!(days_of("last 1 week", today), days_of("last 7 days", today));
+        assert_eq!(
+            days_of("yesterday", date(2024, 3, 1)),
+            (date(2024, 2, 29), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn last_months_from_the_end_of_a_month() {
+        // February has fewer days, the month before March 31 ends on February 29
+        assert_eq!(
+            days_of("last 1 month", date(2024, 3, 31)),
+            (date(2024, 3, 1), date(2024, 3, 31))
+        );
+        assert_eq!(
+            days_of("last 3 months", date(2024, 5, 31)),
+            (date(2024, 3, 1), date(2024, 5, 31))
+        );
+        assert_eq!(
+            days_of("last 12 months", date(2024, 2, 29)),
+            (date(2023, 3, 1), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn months() {
+        assert_eq!(
+            days_of("this month", date(2024, 2, 10)),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            days_of("previous month", date(2024, 3, 31)),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            days_of("previous month", date(2024, 1, 15)),
+            (date(2023, 12, 1), date(2023, 12, 31))
+        );
+        assert_eq!(
+            days_of("month to date", date(2024, 3, 1)),
+            (date(2024, 3, 1), date(2024, 3, 1))
+        );
+    }
+
+    #[test]
+    fn quarters() {
+        assert_eq!(
+            days_of("this quarter", date(2024, 3, 31)),
+            (date(2024, 1, 1), date(2024, 3, 31))
+        );
+        assert_eq!(
+            days_of("this quarter", date(2024, 4, 1)),
+            (date(2024, 4, 1), date(2024, 6, 30))
+        );
+        assert_eq!(
+            days_of("this quarter", date(2024, 11, 30)),
+            (date(2024, 10, 1), date(2024, 12, 31))
+        );
+        assert_eq!(
+            days_of("previous quarter", date(2024, 1, 15)),
+            (date(2023, 10, 1), date(2023, 12, 31))
+        );
+        assert_eq!(
+            days_of("previous quarter", date(2024, 5, 31)),
+            (date(2024, 1, 1), date(2024, 3, 31))
+        );
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(
+            days_of("this year", date(2024, 12, 31)),
+            (date(2024, 1, 1), date(2024, 12, 31))
+        );
+        assert_eq!(
+            days_of("previous year", date(2024, 1, 1)),
+            (date(2023, 1, 1), date(2023, 12, 31))
+        );
+    }
+
+    #[test]
+    fn bounds_cover_whole_days() {
+        let (from, to) = bounds((date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(from, date(2024, 2, 1).and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(
+            to,
+            date(2024, 2, 29)
+                .and_hms_micro_opt(23, 59, 59, 999_999)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn dates_in_the_site_timezone() {
+        let at = |rfc3339: &str, timezone| {
+            let now = chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap();
+            date_at(now.with_timezone(&Utc), timezone)
+        };
+        // Just before and after midnight in UTC
+        assert_eq!(at("2024-03-31T23:59:59Z", None), date(2024, 3, 31));
+        assert_eq!(at("2024-04-01T00:00:00Z", None), date(2024, 4, 1));
+        assert_eq!(
+            at("2024-03-31T16:00:00Z", Some("Asia/Shanghai")),
+            date(2024, 4, 1)
+        );
+        assert_eq!(
+            at("2024-04-01T03:59:59Z", Some("America/New_York")),
+            date(2024, 3, 31)
+        );
+        // UTC+14 and UTC-12 are 26 hours apart, always on different dates
+        assert_eq!(
+            at("2024-01-01T11:00:00Z", Some("Etc/GMT-14")),
+            date(2024, 1, 2)
+        );
+        assert_eq!(
+            at("2024-01-01T11:00:00Z", Some("Etc/GMT+12")),
+            date(2023, 12, 31)
+        );
+    }
+
+    #[test]
+    fn timezones() {
+        assert_eq!(check_timezone("Asia/Shanghai"), Ok(()));
+        assert_eq!(check_timezone("UTC"), Ok(()));
+        assert_eq!(
+            check_timezone("Mars/Olympus_Mons"),
+            Err("unknown timezone `Mars/Olympus_Mons`, e.g. `UTC` or `Asia/Shanghai`".to_owned())
+        );
+    }
+
+    #[test]
+    fn ranges_beyond_the_calendar() {
+        let last = |amount, unit| DateRange::Last { amount, unit };
+        let start = NaiveDate::MIN + Days::new(5);
+        assert_eq!(
+            days(last(DateRange::MAX_AMOUNT, DateUnit::Day), start).0,
+            NaiveDate::MIN
+        );
+        assert_eq!(
+            days(last(u32::MAX, DateUnit::Week), start).0,
+            NaiveDate::MIN
+        );
+        assert_eq!(
+            days(last(u32::MAX, DateUnit::Month), date(2024, 3, 3)).0,
+            NaiveDate::MIN
+        );
+        assert_eq!(
+            days(DateRange::Previous(DatePeriod::Year), NaiveDate::MIN).0,
+            NaiveDate::MIN
+        );
+        assert_eq!(
+            days(DateRange::This(DatePeriod::Year), NaiveDate::MAX).1,
+            NaiveDate::MAX
+        );
+        assert_eq!(
+            picker_days(&NaiveDate::MAX.year().to_string()).map(|(_, last)| last),
+            Some(NaiveDate::MAX)
+        );
+    }
+
+    #[test]
+    fn picker_dates() {
+        assert_eq!(
+            picker_days("2024-02-10"),
+            Some((date(2024, 2, 10), date(2024, 2, 10)))
+        );
+        assert_eq!(
+            picker_days("2024-02"),
+            Some((date(2024, 2, 1), date(2024, 2, 29)))
+        );
+        assert_eq!(
+            picker_days("2024"),
+            Some((date(2024, 1, 1), date(2024, 12, 31)))
+        );
+        assert_eq!(picker_days("2024-13"), None);
+        assert_eq!(picker_days("soon"), None);
+    }
+}
@@ -0,0 +1,222 @@
+use sea_orm::{
+    sea_query::TableRef, ColumnTrait, ColumnType, EntityTrait, IdenStatic, Identity, Iterable,
+    PrimaryKeyToColumn, RelationDef, RelationTrait, RelationType,
+};
+use sea_orm_pro::{ColumnKind, ColumnMeta, EntityMeta, RelationKind, RelationMeta};
+use std::fmt::Debug;
+
+use crate::models;
+
+macro_rules! register_entity_metas {
+    ([$($module:ident),* $(,)?]) => {
+        /// Metadata of all entities exposed in the admin panel
+        pub fn entity_metas() -> Vec<EntityMeta> {
+            let mut entities = vec![$(
+                entity_meta::<
+                    models::$module::Entity,
+                    models::$module::Relation,
+                    models::$module::RelatedEntity,
+                >(),
+            )*];
+            resolve_via(&mut entities);
+            entities
+        }
+    };
+}
+
+register_entity_metas!([
+    address,
+    baker,
+    bakery,
+    cake,
+    cake_baker,
+    customer,
+    customer_address,
+    product,
+    product_category,
+    product_description,
+    product_model,
+    product_model_product_description,
+    sales_order_detail,
+    sales_order_header,
+    user,
+]);
+
+fn entity_meta<E, R, RE>() -> EntityMeta
+where
+    E: EntityTrait,
+    R: RelationTrait,
+    RE: Iterable + Debug,
+{
+    let entity = E::default();
+    let table_name = entity.table_name().to_owned();
+
+    let primary_key: Vec<_> = E::PrimaryKey::iter()
+        .map(|pk| pk.into_column().as_str().to_owned())
+        .collect();
+    let columns = E::Column::iter()
+        .map(|column| {
+            let def = column.def();
+            let (enum_name, enum_values) = match def.get_column_type() {
+                ColumnType::Enum { name, variants } => (
+                    Some(name.to_string()),
+                    variants.iter().map(|variant| variant.to_string()).collect(),
+                ),
+                _ => (None, Vec::new()),
+            };
+            ColumnMeta {
+                name: column.as_str().to_owned(),
+                kind: column_kind(def.get_column_type()),
+                nullable: def.is_null(),
+                primary_key: primary_key.iter().any(|pk| pk == column.as_str()),
+                unique: def.is_unique(),
+                enum_name,
+                enum_values,
+            }
+        })
+        .collect();
+
+    // Relation name exposed by seaography is the `RelatedEntity` variant in snake case.
+    // Variants backed by a `Relation` (or its reverse, for self referencing relations)
+    // point to the table of that relation, otherwise it's a via relation named after
+    // the target entity.
+    let relations = RE::iter()
+        .map(|related| {
+            let variant = format!("{related:?}");
+            let find_relation = |name: &str| R::iter().find(|r| format!("{r:?}") == name);
+            let name = snake_case(&variant);
+            match find_relation(&variant) {
+                Some(relation) => relation_meta(name, relation.def(), false),
+                None => match variant.strip_suffix("Reverse").and_then(find_relation) {
+                    Some(relation) => relation_meta(name, relation.def().rev(), true),
+                    None => RelationMeta {
+                        to_table: name.clone(),
+                        name,
+                        kind: RelationKind::Via,
+                        ..Default::default()
+                    },
+                },
+            }
+        })
+        .collect();
+
+    EntityMeta {
+        table_name,
+        columns,
+        relations,
+    }
+}
+
+fn relation_meta(name: String, def: RelationDef, reverse: bool) -> RelationMeta {
+    let kind = match (def.rel_type, def.is_owner) {
+        // Reverse of a `belongs_to` self referencing relation lists the referencing rows
+        (_, true) if reverse => RelationKind::HasMany,
+        (_, false) => RelationKind::BelongsTo,
+        (RelationType::HasOne, true) => RelationKind::HasOne,
+        (RelationType::HasMany, true) => RelationKind::HasMany,
+    };
+    RelationMeta {
+        name,
+        to_table: table_ref_name(&def.to_tbl),
+        kind,
+        from_columns: column_names(def.from_col),
+        to_columns: column_names(def.to_col),
+        via: None,
+    }
+}
+
+fn column_names(identity: Identity) -> Vec<String> {
+    identity
+        .into_iter()
+        .map(|column| column.to_string())
+        .collect()
+}
+
+/// Find the junction table of via relations, i.e. a table referencing both ends
+fn resolve_via(entities: &mut [EntityMeta]) {
+    let references = |table: &str, to_table: &str| {
+        entities.iter().any(|entity| {
+            entity.table_name == table
+                && entity.relations.iter().any(|relation| {
+                    relation.kind == RelationKind::BelongsTo && relation.to_table == to_table
+                })
+        })
+    };
+    let mut resolved = Vec::new();
+    for (i, entity) in entities.iter().enumerate() {
+        for (j, relation) in entity.relations.iter().enumerate() {
+            if relation.kind != RelationKind::Via {
+                continue;
+            }
+            let via = entity
+                .relations
+                .iter()
+                .filter(|r| r.kind == RelationKind::HasMany)
+                .find(|r| references(&r.to_table, &relation.to_table));
+            if let Some(via) = via {
+                resolved.push((i, j, via.to_table.clone()));
+            }
+        }
+    }
+    for (i, j, via) in resolved {
+        entities[i].relations[j].via = Some(via);
+    }
+}
+
+fn column_kind(column_type: &ColumnType) -> ColumnKind {
+    match column_type {
+        ColumnType::Char(_) | ColumnType::String(_) => ColumnKind::String,
+        ColumnType::Text => ColumnKind::Text,
+        ColumnType::TinyInteger
+        | ColumnType::SmallInteger
+        | ColumnType::Integer
+        | ColumnType::BigInteger
+        | ColumnType::TinyUnsigned
+        | ColumnType::SmallUnsigned
+        | ColumnType::Unsigned
+        | ColumnType::BigUnsigned
+        | ColumnType::Year => ColumnKind::Integer,
+        ColumnType::Float | ColumnType::Double => ColumnKind::Float,
+        ColumnType::Decimal(_) | ColumnType::Money(_) => ColumnKind::Decimal,
+        ColumnType::Boolean => ColumnKind::Boolean,
+        ColumnType::Date => ColumnKind::Date,
+        ColumnType::Time => ColumnKind::Time,
+        ColumnType::DateTime | ColumnType::Timestamp | ColumnType::TimestampWithTimeZone => {
+            ColumnKind::DateTime
+        }
+        ColumnType::Json | ColumnType::JsonBinary => ColumnKind::Json,
+        ColumnType::Uuid => ColumnKind::Uuid,
+        ColumnType::Binary(_) | ColumnType::VarBinary(_) | ColumnType::Blob => ColumnKind::Binary,
+        ColumnType::Enum { .. } => ColumnKind::Enum,
+        _ => ColumnKind::Other,
+    }
+}
+
+fn table_ref_name(table_ref: &TableRef) -> String {
+    match table_ref {
+        TableRef::Table(tbl)
+        | TableRef::SchemaTable(_, tbl)
+        | TableRef::DatabaseSchemaTable(_, _, tbl)
+        | TableRef::TableAlias(tbl, _)
+        | TableRef::SchemaTableAlias(_, tbl, _)
+        | TableRef::DatabaseSchemaTableAlias(_, _, tbl, _) => tbl.to_string(),
+        TableRef::SubQuery(_, alias)
+        | TableRef::ValuesList(_, alias)
+        | TableRef::FunctionCall(_, alias) => alias.to_string(),
+    }
+}
+
+fn snake_case(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 4);
+    for (i, c) in s.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                res.push('_');
+            }
+            res.push(c.to_ascii_lowercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
@@ -0,0 +1,10 @@
+//! Backend support for the admin panel: entity metadata and config loading
+
+pub mod chart;
+pub mod chart_cache;
+pub mod chart_provider;
+pub mod config_store;
+pub mod date_range;
+pub mod entities;
+pub mod permission;
+pub mod virtual_column;
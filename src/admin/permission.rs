@@ -0,0 +1,77 @@
+use casbin::{CachedEnforcer, CoreApi, MgmtApi, RbacApi};
+use sea_orm_pro::{JsonCfg, TableAction};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use super::config_store::ConfigStore;
+
+const ACTIONS: [TableAction; 4] = [
+    TableAction::Read,
+    TableAction::Create,
+    TableAction::Update,
+    TableAction::Delete,
+];
+
+/// Last good config as seen by the user, see [`permitted_config`]
+pub async fn user_config(
+    store: &ConfigStore,
+    enforcer: &RwLock<CachedEnforcer>,
+    user: Option<&str>,
+) -> Option<JsonCfg> {
+    let config = store.config()?;
+    let lock = enforcer.read().await;
+    Some(permitted_config(store, &config, &lock, user))
+}
+
+/// Whether the user may reload the config from the disk, only users with the policy
+/// `(role, config, reload)` of one of their roles can, unlike tables it's closed by default
+pub async fn can_reload_config(enforcer: &RwLock<CachedEnforcer>, user: &str) -> bool {
+    let lock = enforcer.read().await;
+    lock.enforce((user, "config", "reload"))
+        .unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            false
+        })
+}
+
+/// Config as seen by the user signed in, or by a visitor of the login page if there's no user.
+///
+/// A table is open to everyone until a policy names it as object, e.g. `(admin, customer, read)`,
+/// from then on only users with the role or policy for the action can take it.
+pub fn permitted_config(
+    store: &ConfigStore,
+    config: &JsonCfg,
+    enforcer: &CachedEnforcer,
+    user: Option<&str>,
+) -> JsonCfg {
+    let mut decisions = HashMap::new();
+    for entity in store.entities() {
+        let table = entity.table_name.as_str();
+        let governed = !enforcer
+            .get_filtered_policy(1, vec![table.to_owned()])
+            .is_empty();
+        for action in ACTIONS {
+            let allowed = !governed
+                || user.is_some_and(|user| {
+                    enforcer
+                        .enforce((user, table, action.as_str()))
+                        .unwrap_or_else(|err| {
+                            tracing::error!("{err}");
+                            false
+                        })
+                });
+            decisions.insert((table, action), allowed);
+        }
+    }
+    let subjects = match user {
+        Some(user) => {
+            let mut subjects = vec![user.to_owned()];
+            subjects.extend(enforcer.get_implicit_roles_for_user(user, None));
+            subjects
+        }
+        None => Vec::new(),
+    };
+    config.permitted(store.entities(), &subjects, |table, action| {
+        decisions.get(&(table, action)).copied().unwrap_or(true)
+    })
+}
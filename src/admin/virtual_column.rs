@@ -0,0 +1,317 @@
+use chrono::NaiveDate;
+use sea_orm::{
+    prelude::{Date, DateTime, Decimal},
+    sea_query::{
+        Alias, BinOper, Condition, Expr, Func, LikeExpr, Query, SelectStatement, SimpleExpr,
+    },
+    DatabaseBackend, Order as SqlOrder, QueryResult,
+};
+use sea_orm_pro::{
+    BinaryOp, ColumnExpr, DatePart, DateRange, EntityMeta, FilterConditionCfg, FilterOp, Order,
+    TableView, ViewOrderByCfg, VirtualColumnCfg,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::date_range;
+
+/// Most rows of a page, rows are never selected without a limit
+pub const MAX_PAGE_SIZE: u64 = 500;
+
+/// Rows of a table with the values of its virtual columns
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct VirtualQuery {
+    /// Primary key of the rows, e.g. the rows on the current page,
+    /// written as a list of values for a composite primary key
+    pub keys: Vec<Value>,
+    /// Filters on the columns and virtual columns
+    pub filters: Vec<FilterConditionCfg>,
+    /// Sort by a column or a virtual column
+    pub order_by: Option<ViewOrderByCfg>,
+    /// Page number, starting from 1
+    pub page: Option<u64>,
+    /// Number of rows per page, at most [`MAX_PAGE_SIZE`]
+    pub page_size: Option<u64>,
+}
+
+/// SQL expression of a virtual column, in the dialect of the database backend
+pub fn column_expr(expr: &ColumnExpr, table: &str, backend: DatabaseBackend) -> SimpleExpr {
+    let sql = |expr| column_expr(expr, table, backend);
+    match expr {
+        ColumnExpr::Column(name) => Expr::col((Alias::new(table), Alias::new(name))).into(),
+        ColumnExpr::Integer(n) => Expr::val(*n).into(),
+        ColumnExpr::Float(n) => Expr::val(*n).into(),
+        ColumnExpr::String(s) => Expr::val(s.as_str()).into(),
+        ColumnExpr::Neg(expr) => Expr::val(0).sub(sql(expr)),
+        ColumnExpr::Binary(lhs, op, rhs) => {
+            let lhs = Expr::expr(sql(lhs));
+            match op {
+                BinaryOp::Add => lhs.add(sql(rhs)),
+                BinaryOp::Sub => lhs.sub(sql(rhs)),
+                BinaryOp::Mul => lhs.mul(sql(rhs)),
+                BinaryOp::Div => lhs.div(sql(rhs)),
+            }
+        }
+        ColumnExpr::Concat(exprs) => match backend {
+            // `||` is logical OR in MySQL
+            DatabaseBackend::MySql | DatabaseBackend::Postgres => Func::cust(Alias::new("CONCAT"))
+                .args(exprs.iter().map(sql))
+                .into(),
+            DatabaseBackend::Sqlite => exprs
+                .iter()
+                .map(sql)
+                .reduce(|lhs, rhs| lhs.binary(BinOper::Custom("||"), rhs))
+                .unwrap_or_else(|| Expr::val("").into()),
+        },
+        ColumnExpr::Coalesce(exprs) => Func::coalesce(exprs.iter().map(sql)).into(),
+        ColumnExpr::FormatDate(expr, format) => {
+            let format = date_format(format, backend);
+            match backend {
+                DatabaseBackend::MySql => Func::cust(Alias::new("DATE_FORMAT"))
+                    .arg(sql(expr))
+                    .arg(format),
+                DatabaseBackend::Postgres => {
+                    Func::cust(Alias::new("TO_CHAR")).arg(sql(expr)).arg(format)
+                }
+                DatabaseBackend::Sqlite => Func::cust(Alias::new("STRFTIME"))
+                    .arg(format)
+                    .arg(sql(expr)),
+            }
+            .into()
+        }
+    }
+}
+
+/// Date format in the dialect of the database backend
+fn date_format(parts: &[DatePart], backend: DatabaseBackend) -> String {
+    parts
+        .iter()
+        .map(|part| match (part, backend) {
+            (DatePart::Text(text), DatabaseBackend::Postgres) => {
+                // Text is quoted to keep letters from being read as a template pattern
+                format!("\"{}\"", text.replace('"', "\\\""))
+            }
+            (DatePart::Text(text), _) => text.replace('%', "%%"),
+            (DatePart::Year, DatabaseBackend::Postgres) => "YYYY".to_owned(),
+            (DatePart::Month, DatabaseBackend::Postgres) => "MM".to_owned(),
+            (DatePart::Day, DatabaseBackend::Postgres) => "DD".to_owned(),
+            (DatePart::Hour, DatabaseBackend::Postgres) => "HH24".to_owned(),
+            (DatePart::Minute, DatabaseBackend::Postgres) => "MI".to_owned(),
+            (DatePart::Second, DatabaseBackend::Postgres) => "SS".to_owned(),
+            (DatePart::Minute, DatabaseBackend::MySql) => "%i".to_owned(),
+            (DatePart::Second, DatabaseBackend::MySql) => "%s".to_owned(),
+            (DatePart::Year, _) => "%Y".to_owned(),
+            (DatePart::Month, _) => "%m".to_owned(),
+            (DatePart::Day, _) => "%d".to_owned(),
+            (DatePart::Hour, _) => "%H".to_owned(),
+            (DatePart::Minute, _) => "%M".to_owned(),
+            (DatePart::Second, _) => "%S".to_owned(),
+        })
+        .collect()
+}
+
+/// Statements selecting the primary key and the virtual columns of the rows, and counting the rows,
+/// date ranges of the filters are relative to `today`. Only the fields `shown` on the filter panel
+/// are filtered by and only the fields `shown` on the view table are sorted by.
+pub fn select<F>(
+    entity: &EntityMeta,
+    columns: &[VirtualColumnCfg],
+    query: &VirtualQuery,
+    backend: DatabaseBackend,
+    today: NaiveDate,
+    shown: F,
+) -> Result<(SelectStatement, SelectStatement), String>
+where
+    F: Fn(&str, TableView) -> bool,
+{
+    let table = entity.table_name.as_str();
+    let exprs = columns
+        .iter()
+        .map(|column| {
+            let expr = ColumnExpr::parse(&column.expr)
+                .map_err(|err| format!("virtual column `{}`: {err}", column.field))?;
+            Ok((column.field.as_str(), column_expr(&expr, table, backend)))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    // Filter and sort by either a column or a virtual column
+    let field_expr = |field: &str| -> Result<SimpleExpr, String> {
+        match exprs.iter().find(|(name, _)| *name == field) {
+            Some((_, expr)) => Ok(expr.clone()),
+            None if entity.column(field).is_some() => {
+                Ok(Expr::col((Alias::new(table), Alias::new(field))).into())
+            }
+            None => Err(format!(
+                "column `{field}` does not exist on table `{table}`"
+            )),
+        }
+    };
+
+    let mut cond = Condition::all();
+    let primary_key: Vec<_> = entity.primary_key().collect();
+    if !query.keys.is_empty() {
+        let mut keys = Condition::any();
+        for key in query.keys.iter() {
+            let values = match key {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            if values.len() != primary_key.len() {
+                return Err(format!("`{key}` is not a primary key of table `{table}`"));
+            }
+            let mut key_cond = Condition::all();
+            for (column, value) in primary_key.iter().zip(values) {
+                let value = sql_value(value)
+                    .ok_or_else(|| format!("`{key}` is not a primary key of table `{table}`"))?;
+                key_cond = key_cond
+                    .add(Expr::col((Alias::new(table), Alias::new(&column.name))).eq(value));
+            }
+            keys = keys.add(key_cond);
+        }
+        cond = cond.add(keys);
+    }
+    for filter in query.filters.iter() {
+        if !shown(&filter.field, TableView::Filter) {
+            return Err(format!("column `{}` can't be filtered by", filter.field));
+        }
+        cond = cond.add(filter_condition(field_expr(&filter.field)?, filter, today)?);
+    }
+
+    let mut rows = Query::select();
+    rows.from(Alias::new(table)).cond_where(cond.clone());
+    for column in primary_key.iter() {
+        rows.column((Alias::new(table), Alias::new(&column.name)));
+    }
+    for (field, expr) in exprs.iter() {
+        rows.expr_as(expr.clone(), Alias::new(*field));
+    }
+    if let Some(order_by) = &query.order_by {
+        if !shown(&order_by.field, TableView::Table) {
+            return Err(format!("column `{}` can't be sorted by", order_by.field));
+        }
+        let order = match order_by.order {
+            Order::Asc => SqlOrder::Asc,
+            Order::Desc => SqlOrder::Desc,
+        };
+        rows.order_by_expr(field_expr(&order_by.field)?, order);
+    }
+    let page_size = query.page_size.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let page = query.page.unwrap_or(1).max(1);
+    // Offsets are signed in SQL
+    let offset = (page - 1).saturating_mul(page_size).min(i64::MAX as u64);
+    rows.limit(page_size).offset(offset);
+
+    let count = Query::select()
+        .expr_as(Expr::cust("COUNT(*)"), Alias::new("total"))
+        .from(Alias::new(table))
+        .cond_where(cond)
+        .to_owned();
+    Ok((rows, count))
+}
+
+/// Condition of the filter on the column or virtual column, date ranges are relative to `today`
+pub fn filter_condition(
+    expr: SimpleExpr,
+    filter: &FilterConditionCfg,
+    today: NaiveDate,
+) -> Result<SimpleExpr, String> {
+    let expr = Expr::expr(expr);
+    let invalid = |value: &Value| format!("`{value}` is not a value to filter by");
+    let value = |value: &Value| sql_value(value).ok_or_else(|| invalid(value));
+    let values = || match &filter.value {
+        Value::Array(values) => values.iter().map(value).collect::<Result<Vec<_>, _>>(),
+        value => Err(invalid(value)),
+    };
+    // Text matched with `LIKE`, wildcards in the value are matched literally
+    let pattern = |prefix: &str, suffix: &str| {
+        let text = match &filter.value {
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        let text = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        LikeExpr::new(format!("{prefix}{text}{suffix}")).escape('\\')
+    };
+    Ok(match filter.op {
+        FilterOp::Eq => expr.eq(value(&filter.value)?),
+        FilterOp::Ne => expr.ne(value(&filter.value)?),
+        FilterOp::Gt => expr.gt(value(&filter.value)?),
+        FilterOp::Gte => expr.gte(value(&filter.value)?),
+        FilterOp::Lt => expr.lt(value(&filter.value)?),
+        FilterOp::Lte => expr.lte(value(&filter.value)?),
+        FilterOp::Contains => expr.like(pattern("%", "%")),
+        FilterOp::StartsWith => expr.like(pattern("", "%")),
+        FilterOp::EndsWith => expr.like(pattern("%", "")),
+        FilterOp::IsIn => expr.is_in(values()?),
+        FilterOp::IsNotIn => expr.is_not_in(values()?),
+        FilterOp::IsNull => expr.is_null(),
+        FilterOp::IsNotNull => expr.is_not_null(),
+        FilterOp::Between => match <[_; 2]>::try_from(values()?) {
+            Ok([from, to]) => expr.between(from, to),
+            Err(_) => return Err(invalid(&filter.value)),
+        },
+        FilterOp::Last => {
+            let range: DateRange = match &filter.value {
+                Value::String(value) => value.parse()?,
+                value => return Err(invalid(value)),
+            };
+            let (from, to) = date_range::bounds(date_range::days(range, today));
+            expr.between(from, to)
+        }
+    })
+}
+
+/// Names of the fields selected by [`select`]
+pub fn fields<'a>(entity: &'a EntityMeta, columns: &'a [VirtualColumnCfg]) -> Vec<&'a str> {
+    entity
+        .primary_key()
+        .map(|column| column.name.as_str())
+        .chain(columns.iter().map(|column| column.field.as_str()))
+        .collect()
+}
+
+/// JSON object of the fields of the row, values of expressions have no declared type
+/// so they are decoded as the first type that fits
+pub fn to_json(row: &QueryResult, fields: &[&str]) -> Value {
+    let value = |field: &str| -> Value {
+        macro_rules! try_get {
+            ($($ty: ty => $into: expr),* $(,)?) => {
+                $(if let Ok(value) = row.try_get::<Option<$ty>>("", field) {
+                    return value.map($into).unwrap_or_default();
+                })*
+            };
+        }
+        try_get!(
+            i64 => Value::from,
+            i32 => Value::from,
+            i16 => Value::from,
+            f64 => Value::from,
+            Decimal => |n| n.to_string().parse::<f64>().map(Value::from).unwrap_or_default(),
+            String => Value::from,
+            bool => Value::from,
+            DateTime => |d| Value::from(d.to_string()),
+            Date => |d| Value::from(d.to_string()),
+        );
+        Value::Null
+    };
+    let row: Map<String, Value> = fields
+        .iter()
+        .map(|field| (field.to_string(), value(field)))
+        .collect();
+    row.into()
+}
+
+/// SQL value of a scalar JSON value
+pub fn sql_value(value: &Value) -> Option<sea_orm::Value> {
+    match value {
+        Value::String(s) => Some(s.clone().into()),
+        Value::Bool(b) => Some((*b).into()),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => Some(n.into()),
+            None => n.as_f64().map(Into::into),
+        },
+        _ => None,
+    }
+}
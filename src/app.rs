@@ -15,7 +15,14 @@ use loco_rs::{
 };
 use migration::Migrator;
 
-use crate::{controllers, initializers::casbin_enforcer::CasbinEnforcerInitializer, tasks};
+use crate::{
+    charts, controllers,
+    initializers::{
+        casbin_enforcer::CasbinEnforcerInitializer, chart_registry::ChartRegistryInitializer,
+        config_store::ConfigStoreInitializer,
+    },
+    tasks,
+};
 
 pub struct App;
 #[async_trait]
@@ -66,6 +73,9 @@ impl Hooks for App {
                 // Some(vec![controllers::album::api_routes()]),
             )),
             Box::new(CasbinEnforcerInitializer),
+            Box::new(ConfigStoreInitializer),
+            // Charts of the dashboards computed in Rust
+            Box::new(ChartRegistryInitializer::new(charts::registry())),
         ])
     }
 
@@ -78,6 +88,7 @@ impl Hooks for App {
             .add_route(controllers::upload::routes())
             .add_route(controllers::graphql::routes())
             .add_route(controllers::admin::routes())
+            .add_route(controllers::saved_filter::routes())
     }
 
     async fn after_context(ctx: AppContext) -> Result<AppContext> {
@@ -102,6 +113,9 @@ impl Hooks for App {
     fn register_tasks(tasks: &mut Tasks) {
         // Register all tasks
         tasks.register(tasks::seed::SeedData);
+        tasks.register(tasks::raw_table_config::GenerateRawTables);
+        tasks.register(tasks::composite_table_config::GenerateCompositeTable);
+        tasks.register(tasks::config_schema::ExportConfigSchema);
     }
 
     async fn truncate(_ctx: &AppContext) -> Result<()> {
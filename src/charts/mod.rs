@@ -0,0 +1,12 @@
+//! Charts of the dashboards computed in Rust, registered with the dashboard endpoint in `App::initializers`
+
+mod product_by_product_category;
+
+use crate::admin::chart_provider::ChartRegistry;
+
+pub use product_by_product_category::ProductByProductCategory;
+
+/// Chart providers of the app
+pub fn registry() -> ChartRegistry {
+    ChartRegistry::new().register(ProductByProductCategory)
+}
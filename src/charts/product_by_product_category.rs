@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use loco_rs::Result;
+use sea_orm::{
+    sea_query::{Alias, Asterisk, Expr, Func},
+    DbConn, EntityTrait, QueryOrder, QuerySelect,
+};
+
+use crate::{
+    admin::{
+        chart::{DashboardBody, Datum},
+        chart_provider::ChartProvider,
+    },
+    models::{product, product_category},
+};
+
+/// Number of products in each product category
+pub struct ProductByProductCategory;
+
+#[async_trait]
+impl ChartProvider for ProductByProductCategory {
+    fn name(&self) -> &str {
+        "product_by_product_category"
+    }
+
+    fn tables(&self) -> &[&str] {
+        &["product", "product_category"]
+    }
+
+    async fn data(&self, db: &DbConn, _body: &DashboardBody) -> Result<Vec<Datum>> {
+        let data = product_category::Entity::find()
+            .select_only()
+            .column(product_category::Column::Name)
+            .column_as(Func::count(Expr::col(Asterisk)), "val")
+            .left_join(product::Entity)
+            .group_by(product_category::Column::Name)
+            .order_by_desc(Expr::col(Alias::new("val")))
+            .into_tuple::<(String, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(key, val)| Datum::new(key, val as f64))
+            .collect();
+        Ok(data)
+    }
+}
@@ -1,197 +1,365 @@
-use loco_rs::{environment::Environment, prelude::*};
-use migration::{IntoColumnRef, IntoIden};
-use sea_orm::{
-    prelude::DateTime,
-    sea_query::{Alias, Asterisk, Expr, Func, SimpleExpr},
-    DatabaseBackend, DbConn, DeriveCustomColumn, FromQueryResult, IdenStatic, QueryOrder,
-    QuerySelect,
+use axum::{
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    Extension,
 };
-use sea_orm_pro::{ConfigParser, JsonCfg};
-use seaography::lazy_static;
-use serde::{Deserialize, Serialize};
+use casbin::CachedEnforcer;
+use loco_rs::{auth::jwt, prelude::*};
+use sea_orm::{ConnectionTrait, DatabaseConnection, JsonValue};
+use sea_orm_pro::{ChartQueryCfg, ConfigSchema, DashboardChart, TimeBucket, DEFAULT_DASHBOARD};
+use serde::Serialize;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::RwLock;
 
-use crate::models::{customer, product, product_category, sales_order_detail, sales_order_header};
+use super::Res;
+use crate::admin::{
+    chart::{self, ComparedData, Comparison, DashboardBody, Datum},
+    chart_cache,
+    chart_provider::ChartRegistry,
+    config_store::ConfigStore,
+    date_range, permission,
+    virtual_column::{self, VirtualQuery},
+};
+
+/// Email of the user signed in, the config is fetched by the login page as well
+fn signed_in_user(ctx: &AppContext, headers: &HeaderMap) -> Option<String> {
+    let token = headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    let jwt_config = ctx.config.get_jwt_config().ok()?;
+    let token = jwt::JWT::new(&jwt_config.secret).validate(token).ok()?;
+    Some(token.claims.pid)
+}
 
-const CONFIG_ROOT: &str = "pro_admin";
+pub async fn config(
+    State(ctx): State<AppContext>,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    // Serve the last good config, the store is kept up to date by the file watcher or reload
+    match (store.config(), store.error()) {
+        (Some(config), _) => {
+            // Tables and columns the user isn't allowed to see are left out
+            let user = signed_in_user(&ctx, &headers);
+            let lock = enforcer.read().await;
+            let mut config = permission::permitted_config(&store, &config, &lock, user.as_deref());
+            drop(lock);
+            // Date pickers open on the default date range as of today
+            let today = date_range::today(config.site.timezone.as_deref());
+            date_range::resolve_default_dates(&mut config, today);
+            format::json(config)
+        }
+        // Config never loaded successfully, report the error to the developer
+        (None, Some(err)) => format::render()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(&*err),
+        (None, None) => not_found(),
+    }
+}
 
-lazy_static::lazy_static! {
-    static ref CONFIG: JsonCfg = ConfigParser::new().load_config(CONFIG_ROOT).unwrap();
+pub async fn reload_config(
+    auth: auth::JWT,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+) -> Result<Response> {
+    if !permission::can_reload_config(&enforcer, &auth.claims.pid).await {
+        return format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(Res::fail("Not allowed to reload the config"));
+    }
+    match store.reload() {
+        Ok(_) => format::json(Res::success("Config reloaded")),
+        Err(err) => {
+            tracing::error!("{err}");
+            format::render()
+                .status(StatusCode::UNPROCESSABLE_ENTITY)
+                .json(&*err)
+        }
+    }
 }
 
-pub async fn config(State(ctx): State<AppContext>) -> Result<Response> {
-    if ctx.environment == Environment::Production {
-        // Release: load config from the disk once and then return the cached config afterwards
-        format::json(&*CONFIG)
-    } else {
-        // Debug: load config from disk on every request
-        let config = ConfigParser::new()
-            .load_config(CONFIG_ROOT)
-            .map_err(Into::<Box<dyn std::error::Error + Send + Sync>>::into)?;
-        format::json(config)
+/// JSON Schema of the config file, i.e. `config`, `dashboard`, `raw_table` or `composite_table`
+pub async fn config_schema(Path(name): Path<String>) -> Result<Response> {
+    let name = name.trim_end_matches(".json").trim_end_matches(".schema");
+    match ConfigSchema::from_name(name) {
+        Some(schema) => format::json(schema.schema()),
+        None => not_found(),
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct DashboardBody {
-    pub graph: String,
-    pub from: Option<DateTime>,
-    pub to: Option<DateTime>,
+#[derive(Debug, Serialize)]
+pub struct VirtualRows {
+    pub items: Vec<JsonValue>,
+    pub total: i64,
 }
 
-#[derive(Debug, Deserialize, Serialize, FromQueryResult, PartialEq)]
-pub struct Datum {
-    pub key: String,
-    pub val: i32,
+/// Primary key and virtual columns of the rows, filtered and sorted by the columns
+/// the user can see on the table
+pub async fn virtual_columns(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+    Path(table): Path<String>,
+    Json(body): Json<VirtualQuery>,
+) -> Result<Response> {
+    let Some(entity) = store
+        .entities()
+        .iter()
+        .find(|entity| entity.table_name == table)
+    else {
+        return not_found();
+    };
+    // Tables the user can't read are left out of the config
+    let config = permission::user_config(&store, &enforcer, Some(&auth.claims.pid)).await;
+    let Some(config) = config.filter(|config| {
+        config
+            .table_configs(store.entities(), &table)
+            .next()
+            .is_some()
+    }) else {
+        return format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(Res::fail("Not allowed to read the table"));
+    };
+    let columns = config.virtual_columns(store.entities(), &table);
+    let backend = ctx.db.get_database_backend();
+    let shown = |field: &str, view| config.shows_column(store.entities(), &table, field, view);
+    let (rows, count) =
+        virtual_column::select(entity, &columns, &body, backend, store.today(), shown)
+            .map_err(Error::BadRequest)?;
+
+    let fields = virtual_column::fields(entity, &columns);
+    let items = ctx
+        .db
+        .query_all(backend.build(&rows))
+        .await?
+        .iter()
+        .map(|row| virtual_column::to_json(row, &fields))
+        .collect();
+    let total = match ctx.db.query_one(backend.build(&count)).await? {
+        Some(row) => row.try_get("", "total")?,
+        None => 0,
+    };
+    format::json(VirtualRows { items, total })
 }
 
-#[derive(Copy, Clone, Debug, DeriveCustomColumn)]
-pub enum DatumColumn {
-    Key,
-    Val,
+#[derive(Debug, Serialize)]
+pub struct DashboardItem {
+    pub name: String,
+    pub title: String,
+    pub subtitle: String,
 }
 
-impl IdenStatic for DatumColumn {
-    fn as_str(&self) -> &str {
-        self.default_as_str()
-    }
+/// Dashboards available to the user
+pub async fn dashboards(
+    auth: auth::JWT,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+) -> Result<Response> {
+    let Some(config) = permission::user_config(&store, &enforcer, Some(&auth.claims.pid)).await
+    else {
+        return not_found();
+    };
+    let dashboards: Vec<_> = std::iter::once((DEFAULT_DASHBOARD, &config.dashboard))
+        .chain(
+            config
+                .dashboards
+                .iter()
+                .map(|(name, dashboard)| (name.as_str(), dashboard)),
+        )
+        .map(|(name, dashboard)| DashboardItem {
+            name: name.to_owned(),
+            title: dashboard.title.clone(),
+            subtitle: dashboard.subtitle.clone(),
+        })
+        .collect();
+    format::json(dashboards)
+}
+
+/// Chart missing on the dashboard, with the charts that can be fetched instead
+#[derive(Debug, Serialize)]
+pub struct ChartNotFound {
+    pub message: String,
+    pub available: Vec<String>,
 }
 
 pub async fn dashboard(
-    _auth: auth::JWT,
+    auth: auth::JWT,
     State(ctx): State<AppContext>,
-    Json(body): Json<DashboardBody>,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+    Extension(registry): Extension<Arc<ChartRegistry>>,
+    Json(mut body): Json<DashboardBody>,
 ) -> Result<Response> {
+    // Only charts on a dashboard available to the user
+    let config = permission::user_config(&store, &enforcer, Some(&auth.claims.pid)).await;
+    let dashboard = config
+        .as_ref()
+        .and_then(|config| config.find_dashboard(body.dashboard.as_deref()));
+    let chart = dashboard.and_then(|dashboard| dashboard.find_chart(&body.graph));
+    if let (Some(config), Some(chart)) = (&config, chart) {
+        // Bounds left out of the request are the defaults of the chart
+        let today = date_range::today(config.site.timezone.as_deref());
+        let (from, to) = date_range::default_bounds(chart, today);
+        body.from = body.from.or(from);
+        body.to = body.to.or(to);
+    }
     let db = &ctx.db;
-    let data = match body.graph.as_str() {
-        "new_customer_by_month" => {
-            customer::Entity::find()
-                .select_only()
-                .column_as(
-                    Expr::expr(cast_as_year_month(db, customer::Column::CreatedDate)),
-                    DatumColumn::Key,
-                )
-                .column_as(
-                    Expr::expr(Func::cast_as(
-                        Func::count(Expr::col(Asterisk)),
-                        int_keyword(db),
-                    )),
-                    DatumColumn::Val,
-                )
-                .filter(customer::Column::CreatedDate.gte(body.from.unwrap()))
-                .filter(customer::Column::CreatedDate.lte(body.to.unwrap()))
-                .group_by(Expr::col(DatumColumn::Key))
-                .into_model::<Datum>()
-                .all(db)
-                .await?
-        }
-        "sales_value_by_day" => {
-            sales_order_detail::Entity::find()
-                .select_only()
-                .column_as(
-                    Expr::expr(cast_as_day(
-                        db,
-                        (
-                            sales_order_header::Entity,
-                            sales_order_header::Column::OrderDate,
-                        ),
-                    )),
-                    DatumColumn::Key,
-                )
-                .column_as(
-                    Expr::expr(Func::cast_as(
-                        Func::sum(
-                            Expr::col(sales_order_detail::Column::UnitPrice)
-                                .mul(Expr::col(sales_order_detail::Column::OrderQty)),
-                        ),
-                        int_keyword(db),
-                    )),
-                    DatumColumn::Val,
-                )
-                .left_join(sales_order_header::Entity)
-                .filter(
-                    Expr::col((
-                        sales_order_header::Entity,
-                        sales_order_header::Column::OrderDate,
-                    ))
-                    .gte(body.from.unwrap()),
-                )
-                .filter(
-                    Expr::col((
-                        sales_order_header::Entity,
-                        sales_order_header::Column::OrderDate,
-                    ))
-                    .lte(body.to.unwrap()),
-                )
-                .group_by(Expr::col(DatumColumn::Key))
-                .into_model::<Datum>()
-                .all(db)
-                .await?
+    if let Some(chart) = chart {
+        // Only charts with a TTL are cached
+        let cached = match chart.cache_ttl {
+            Some(ttl) => {
+                let tables = chart_cache::chart_tables(store.entities(), &registry, chart);
+                let key = chart_cache::key(&ctx.cache, chart, &body, &tables).await;
+                if let Some(data) = chart_cache::get(&ctx.cache, &key).await {
+                    return format::json(data);
+                }
+                Some((key, Duration::from_secs(ttl)))
+            }
+            None => None,
+        };
+        if let Some(mut data) = chart_data(db, &store, &registry, chart, &body).await? {
+            // Filters of the raw table behind each value
+            let bucket = match &chart.query {
+                Some(query) => key_bucket(&store, chart, query, &body),
+                None => body.timescale.or(chart.time_bucket().and_then(Result::ok)),
+            };
+            chart::drill_down(store.entities(), chart, bucket, &mut data);
+            let data = match body.compare {
+                Some(comparison) => serde_json::to_value(
+                    compare_chart(db, &store, &registry, chart, &body, data, comparison).await?,
+                )?,
+                None => serde_json::to_value(data)?,
+            };
+            if let Some((key, ttl)) = cached {
+                chart_cache::insert(&ctx.cache, &key, &data, ttl).await;
+            }
+            return format::json(data);
         }
-        "product_by_product_category" => {
-            product_category::Entity::find()
-                .select_only()
-                .column_as(
-                    Expr::expr(Expr::col((
-                        product_category::Entity,
-                        product_category::Column::Name,
-                    ))),
-                    DatumColumn::Key,
-                )
-                .column_as(
-                    Expr::expr(Func::cast_as(
-                        Func::count(Expr::col(Asterisk)),
-                        int_keyword(db),
-                    )),
-                    DatumColumn::Val,
-                )
-                .left_join(product::Entity)
-                .group_by(Expr::col(DatumColumn::Key))
-                .order_by_desc(Expr::col(DatumColumn::Val))
-                .into_model::<Datum>()
-                .all(db)
-                .await?
-        }
-        _ => not_found()?,
+    }
+
+    // Charts of the dashboard with a query or a provider to compute them
+    let available = dashboard
+        .map(|dashboard| {
+            dashboard
+                .charts()
+                .filter(|chart| chart.query.is_some() || registry.get(&chart.chart).is_some())
+                .map(|chart| chart.chart.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let message = match (dashboard, chart) {
+        (None, _) => format!(
+            "dashboard `{}` not found",
+            body.dashboard.as_deref().unwrap_or(DEFAULT_DASHBOARD)
+        ),
+        (Some(_), None) => format!("chart `{}` is not on the dashboard", body.graph),
+        (Some(_), Some(_)) => format!("chart `{}` has no query or provider", body.graph),
     };
-    format::json(data)
+    format::render()
+        .status(StatusCode::NOT_FOUND)
+        .json(ChartNotFound { message, available })
 }
 
-fn cast_as_year_month(db: &DbConn, col: impl IntoColumnRef) -> SimpleExpr {
-    let func = match db.get_database_backend() {
-        DatabaseBackend::MySql => Func::cust(Alias::new("DATE_FORMAT"))
-            .arg(Expr::col(col.into_column_ref()))
-            .arg("%Y-%m"),
-        DatabaseBackend::Postgres => Func::cust(Alias::new("TO_CHAR"))
-            .arg(Expr::col(col.into_column_ref()))
-            .arg("YYYY-mm"),
-        DatabaseBackend::Sqlite => Func::cust(Alias::new("STRFTIME"))
-            .arg("%Y-%m")
-            .arg(Expr::col(col.into_column_ref())),
-    };
-    func.into()
+/// Data of the chart, from the query of the chart config or the chart provider
+async fn chart_data(
+    db: &DatabaseConnection,
+    store: &ConfigStore,
+    registry: &ChartRegistry,
+    chart: &DashboardChart,
+    body: &DashboardBody,
+) -> Result<Option<Vec<Datum>>> {
+    if let Some(query) = &chart.query {
+        return query_data(db, store, chart, query, body).await.map(Some);
+    }
+    match registry.get(&chart.chart) {
+        Some(provider) => Ok(Some(provider.data(db, body).await?)),
+        None => Ok(None),
+    }
 }
 
-fn cast_as_day(db: &DbConn, col: impl IntoColumnRef) -> SimpleExpr {
-    let func = match db.get_database_backend() {
-        DatabaseBackend::MySql => Func::cust(Alias::new("DATE_FORMAT"))
-            .arg(Expr::col(col.into_column_ref()))
-            .arg("%Y-%m-%d"),
-        DatabaseBackend::Postgres => Func::cust(Alias::new("TO_CHAR"))
-            .arg(Expr::col(col.into_column_ref()))
-            .arg("YYYY-mm-dd"),
-        DatabaseBackend::Sqlite => Func::cust(Alias::new("STRFTIME"))
-            .arg("%Y-%m-%d")
-            .arg(Expr::col(col.into_column_ref())),
-    };
-    func.into()
+async fn query_data(
+    db: &DatabaseConnection,
+    store: &ConfigStore,
+    chart: &DashboardChart,
+    query: &ChartQueryCfg,
+    body: &DashboardBody,
+) -> Result<Vec<Datum>> {
+    let bucket = key_bucket(store, chart, query, body);
+    let stmt = chart::select(
+        db,
+        store.entities(),
+        query,
+        bucket,
+        body.from,
+        body.to,
+        store.today(),
+    )
+    .map_err(Error::BadRequest)?;
+    let rows = db.query_all(db.get_database_backend().build(&stmt)).await?;
+    Ok(chart::data(query, bucket, &rows, body.from, body.to)?)
 }
 
-fn int_keyword(db: &DbConn) -> impl IntoIden {
-    match db.get_database_backend() {
-        DatabaseBackend::MySql => Alias::new("SIGNED INTEGER"),
-        DatabaseBackend::Postgres => Alias::new("INT4"),
-        DatabaseBackend::Sqlite => Alias::new("INT"),
-    }
+fn key_bucket(
+    store: &ConfigStore,
+    chart: &DashboardChart,
+    query: &ChartQueryCfg,
+    body: &DashboardBody,
+) -> Option<TimeBucket> {
+    let timescale = chart.time_bucket().and_then(Result::ok);
+    chart::key_bucket(store.entities(), query, body.timescale, timescale)
+}
+
+/// Data of the chart next to the data of the compared period, with the totals of both
+async fn compare_chart(
+    db: &DatabaseConnection,
+    store: &ConfigStore,
+    registry: &ChartRegistry,
+    chart: &DashboardChart,
+    body: &DashboardBody,
+    data: Vec<Datum>,
+    comparison: Comparison,
+) -> Result<ComparedData> {
+    let (Some(from), Some(to)) = (body.from, body.to) else {
+        return Err(Error::BadRequest(
+            "comparison needs the start and end of the chart".to_owned(),
+        ));
+    };
+    let (from, to) = comparison.bounds(from, to).ok_or_else(|| {
+        Error::BadRequest("the compared period is before the first day of the calendar".to_owned())
+    })?;
+    let compared_body = DashboardBody {
+        from: Some(from),
+        to: Some(to),
+        ..body.clone()
+    };
+    let compared = chart_data(db, store, registry, chart, &compared_body)
+        .await?
+        .unwrap_or_default();
+
+    let (totals, compared_totals, bucketed) = match &chart.query {
+        // Totals of averages and the like are aggregated over the rows, not summed up
+        Some(query) => {
+            let totals_query = chart::totals_query(query);
+            (
+                query_data(db, store, chart, &totals_query, body).await?,
+                query_data(db, store, chart, &totals_query, &compared_body).await?,
+                key_bucket(store, chart, query, body).is_some(),
+            )
+        }
+        None => (
+            chart::sum_series(&data),
+            chart::sum_series(&compared),
+            chart.timescale().is_some(),
+        ),
+    };
+    Ok(ComparedData {
+        totals: chart::totals(&totals, &compared_totals),
+        data: chart::compare(data, &compared, bucketed),
+    })
 }
 
 pub fn routes() -> Routes {
@@ -200,6 +368,14 @@ pub fn routes() -> Routes {
         .prefix("admin")
         // Fetch web config
         .add("/config", get(config))
+        // Reload web config from the disk, for users with the `config` `reload` policy
+        .add("/config/reload", post(reload_config))
+        // Fetch JSON Schema of the config files
+        .add("/config/schema/{name}", get(config_schema))
+        // Fetch virtual columns of the table
+        .add("/virtual_columns/{table}", post(virtual_columns))
+        // List dashboards available to the user
+        .add("/dashboards", get(dashboards))
         // Fetch dashboard graph data
         .add("/dashboard", post(dashboard))
 }
@@ -1,10 +1,11 @@
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use axum::{body::Body, extract::Request};
+use axum::{body::Body, extract::Request, Extension};
 use loco_rs::prelude::*;
 use seaography::async_graphql;
+use std::sync::Arc;
 use tower_service::Service;
 
-use crate::graphql::query_root;
+use crate::{admin::config_store::ConfigStore, graphql::query_root};
 
 async fn graphql_playground() -> Result<Response> {
     // Setup GraphQL playground web and specify the endpoint for GraphQL resolver
@@ -22,6 +23,7 @@ async fn graphql_playground() -> Result<Response> {
 async fn graphql_handler(
     _auth: auth::JWT,
     State(ctx): State<AppContext>,
+    Extension(store): Extension<Arc<ConfigStore>>,
     req: Request<Body>,
 ) -> Result<Response> {
     // Maximum depth of the constructed query
@@ -29,7 +31,8 @@ async fn graphql_handler(
     // Maximum complexity of the constructed query
     const COMPLEXITY: Option<usize> = None;
     // GraphQL schema
-    let schema = query_root::schema(ctx.db.clone(), DEPTH, COMPLEXITY).unwrap();
+    let schema =
+        query_root::schema(ctx.db.clone(), DEPTH, COMPLEXITY, store, ctx.cache.clone()).unwrap();
     // GraphQL handler
     let mut graphql_handler = async_graphql_axum::GraphQL::new(schema);
     let res = graphql_handler.call(req).await.unwrap();
@@ -1,6 +1,7 @@
 pub mod admin;
 pub mod auth;
 pub mod graphql;
+pub mod saved_filter;
 pub mod upload;
 pub mod user;
 
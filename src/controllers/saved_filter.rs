@@ -0,0 +1,204 @@
+use axum::{extract::Query, http::StatusCode, Extension};
+use casbin::CachedEnforcer;
+use loco_rs::prelude::*;
+use sea_orm::QueryOrder;
+use sea_orm_pro::FilterConditionCfg;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::Res;
+use crate::{
+    admin::{config_store::ConfigStore, permission},
+    models::{saved_filter, user},
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListParams {
+    /// Only the filters of the table
+    pub table: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedFilterParams {
+    pub table: String,
+    pub name: String,
+    pub conditions: Vec<FilterConditionCfg>,
+}
+
+/// User signed in, filters are saved per user
+async fn current_user(auth: &auth::JWT, ctx: &AppContext) -> Result<user::Model> {
+    user::Entity::find()
+        .filter(user::Column::Email.eq(&auth.claims.pid))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::Unauthorized("unauthorized!".to_owned()))
+}
+
+/// Saved filter of the user, filters of other users are not found
+async fn find_filter(ctx: &AppContext, user: &user::Model, id: i32) -> Result<saved_filter::Model> {
+    saved_filter::Entity::find_by_id(id)
+        .filter(saved_filter::Column::UserId.eq(user.id))
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+/// Names of the filters of a table are unique per user, `id` is the filter being updated
+async fn check_name(
+    ctx: &AppContext,
+    user: &user::Model,
+    params: &SavedFilterParams,
+    id: Option<i32>,
+) -> Result<()> {
+    let mut query = saved_filter::Entity::find()
+        .filter(saved_filter::Column::UserId.eq(user.id))
+        .filter(saved_filter::Column::TableName.eq(&params.table))
+        .filter(saved_filter::Column::Name.eq(&params.name));
+    if let Some(id) = id {
+        query = query.filter(saved_filter::Column::Id.ne(id));
+    }
+    if query.one(&ctx.db).await?.is_some() {
+        return Err(Error::BadRequest(format!(
+            "filter `{}` already exists",
+            params.name
+        )));
+    }
+    Ok(())
+}
+
+/// Whether the user can read the table, tables the user can't read are left out of the config
+/// as in `/config`
+async fn can_read_table(
+    store: &ConfigStore,
+    enforcer: &RwLock<CachedEnforcer>,
+    auth: &auth::JWT,
+    table: &str,
+) -> bool {
+    permission::user_config(store, enforcer, Some(&auth.claims.pid))
+        .await
+        .is_some_and(|config| {
+            config
+                .table_configs(store.entities(), table)
+                .next()
+                .is_some()
+        })
+}
+
+fn forbidden() -> Result<Response> {
+    format::render()
+        .status(StatusCode::FORBIDDEN)
+        .json(Res::fail("Not allowed to read the table"))
+}
+
+/// Check the conditions against the columns of the table
+fn check_params(store: &ConfigStore, params: &SavedFilterParams) -> Result<serde_json::Value> {
+    let Some(entity) = store
+        .entities()
+        .iter()
+        .find(|entity| entity.table_name == params.table)
+    else {
+        return Err(Error::BadRequest(format!(
+            "table `{}` does not exist",
+            params.table
+        )));
+    };
+    if params.name.trim().is_empty() {
+        return Err(Error::BadRequest("name is required".to_owned()));
+    }
+    for condition in params.conditions.iter() {
+        condition.check(entity).map_err(Error::BadRequest)?;
+    }
+    Ok(serde_json::to_value(&params.conditions)?)
+}
+
+pub async fn list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListParams>,
+) -> Result<Response> {
+    let user = current_user(&auth, &ctx).await?;
+    let mut query = saved_filter::Entity::find().filter(saved_filter::Column::UserId.eq(user.id));
+    if let Some(table) = params.table {
+        query = query.filter(saved_filter::Column::TableName.eq(table));
+    }
+    let filters = query
+        .order_by_asc(saved_filter::Column::TableName)
+        .order_by_asc(saved_filter::Column::Name)
+        .all(&ctx.db)
+        .await?;
+    format::json(filters)
+}
+
+pub async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+    Json(params): Json<SavedFilterParams>,
+) -> Result<Response> {
+    let user = current_user(&auth, &ctx).await?;
+    let conditions = check_params(&store, &params)?;
+    if !can_read_table(&store, &enforcer, &auth, &params.table).await {
+        return forbidden();
+    }
+    check_name(&ctx, &user, &params, None).await?;
+    let filter = saved_filter::ActiveModel {
+        user_id: ActiveValue::set(user.id),
+        table_name: ActiveValue::set(params.table),
+        name: ActiveValue::set(params.name),
+        conditions: ActiveValue::set(conditions),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await?;
+    format::json(filter)
+}
+
+pub async fn update(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Extension(store): Extension<Arc<ConfigStore>>,
+    Extension(enforcer): Extension<Arc<RwLock<CachedEnforcer>>>,
+    Path(id): Path<i32>,
+    Json(params): Json<SavedFilterParams>,
+) -> Result<Response> {
+    let user = current_user(&auth, &ctx).await?;
+    let filter = find_filter(&ctx, &user, id).await?;
+    let conditions = check_params(&store, &params)?;
+    if !can_read_table(&store, &enforcer, &auth, &params.table).await {
+        return forbidden();
+    }
+    check_name(&ctx, &user, &params, Some(id)).await?;
+    let mut filter: saved_filter::ActiveModel = filter.into();
+    filter.table_name = ActiveValue::set(params.table);
+    filter.name = ActiveValue::set(params.name);
+    filter.conditions = ActiveValue::set(conditions);
+    filter.updated_at = ActiveValue::set(chrono::Utc::now().naive_utc());
+    let filter = filter.update(&ctx.db).await?;
+    format::json(filter)
+}
+
+pub async fn remove(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let user = current_user(&auth, &ctx).await?;
+    find_filter(&ctx, &user, id).await?.delete(&ctx.db).await?;
+    format::json(Res::success("Filter deleted"))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        // Saved filters of the user signed in
+        .prefix("admin/saved_filters")
+        // List saved filters, of a table with `?table=`
+        .add("/", get(list))
+        // Save a filter
+        .add("/", post(create))
+        // Update a saved filter
+        .add("/{id}", put(update))
+        // Delete a saved filter
+        .add("/{id}", delete(remove))
+}
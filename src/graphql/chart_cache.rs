@@ -0,0 +1,81 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery},
+    parser::types::{ExecutableDocument, OperationType, Selection},
+    Response, ServerResult, Variables,
+};
+use loco_rs::cache::Cache;
+use seaography::async_graphql;
+use std::sync::{Arc, Mutex};
+
+use crate::admin::chart_cache;
+
+/// Mutations changing the rows of the table, by suffix of the mutation name
+const MUTATIONS: [&str; 4] = ["_create_one", "_create_batch", "_update", "_delete"];
+
+/// Drop the cached chart data computed from the tables changed by mutations
+pub struct ChartCacheInvalidation {
+    pub cache: Arc<Cache>,
+}
+
+impl ExtensionFactory for ChartCacheInvalidation {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ChartCacheExtension {
+            cache: self.cache.clone(),
+            tables: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+struct ChartCacheExtension {
+    cache: Arc<Cache>,
+    /// Tables changed by the mutations of the request
+    tables: Mutex<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for ChartCacheExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let tables = document
+            .operations
+            .iter()
+            .filter(|(_, operation)| matches!(operation.node.ty, OperationType::Mutation))
+            .flat_map(|(_, operation)| operation.node.selection_set.node.items.iter())
+            .filter_map(|selection| match &selection.node {
+                Selection::Field(field) => {
+                    let name = field.node.name.node.as_str();
+                    MUTATIONS
+                        .iter()
+                        .find_map(|suffix| name.strip_suffix(suffix))
+                        .map(ToOwned::to_owned)
+                }
+                _ => None,
+            });
+        if let Ok(mut changed) = self.tables.lock() {
+            changed.extend(tables);
+        }
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let response = next.run(ctx, operation_name).await;
+        // Rows may be written even if the mutation fails halfway
+        let tables = match self.tables.lock() {
+            Ok(mut changed) => std::mem::take(&mut *changed),
+            Err(_) => Vec::new(),
+        };
+        chart_cache::invalidate(&self.cache, tables.iter().map(String::as_str)).await;
+        response
+    }
+}
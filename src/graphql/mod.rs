@@ -1 +1,3 @@
+pub mod chart_cache;
 pub mod query_root;
+pub mod validation;
@@ -4,6 +4,7 @@ use async_graphql::{
     parser::types::{ExecutableDocument, OperationType},
     ServerResult, Variables,
 };
+use loco_rs::cache::Cache;
 use sea_orm::DatabaseConnection;
 use seaography::{
     async_graphql::{self, ServerError},
@@ -11,8 +12,11 @@ use seaography::{
 };
 use std::{env, sync::Arc};
 
+use super::{chart_cache::ChartCacheInvalidation, validation::Validation};
+use crate::admin::config_store::ConfigStore;
+
 lazy_static::lazy_static! {
-    static ref CONTEXT: BuilderContext = BuilderContext::default();
+    pub static ref CONTEXT: BuilderContext = BuilderContext::default();
     static ref DEMO_SITE: bool = env::var_os("DEMO_SITE").unwrap_or_default() == "true";
 }
 
@@ -20,6 +24,8 @@ pub fn schema(
     database: DatabaseConnection,
     depth: Option<usize>,
     complexity: Option<usize>,
+    store: Arc<ConfigStore>,
+    cache: Arc<Cache>,
 ) -> Result<Schema, SchemaError> {
     // Construct GraphQL schema
     let builder = Builder::new(&CONTEXT, database.clone());
@@ -33,6 +39,10 @@ pub fn schema(
         // GraphQL schema with database connection
         .data(database)
         .extension(Readonly)
+        // Reject values the admin panel config doesn't accept
+        .extension(Validation { store })
+        // Cached chart data of the changed tables is out of date
+        .extension(ChartCacheInvalidation { cache })
         .finish()
 }
 
@@ -0,0 +1,360 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery},
+    parser::types::{ExecutableDocument, OperationType, Selection},
+    ErrorExtensionValues, Name, Pos, ServerResult, Value, Variables,
+};
+use chrono::NaiveDate;
+use sea_orm::{
+    sea_query::{Alias, Condition, Expr, Query, SelectStatement, SimpleExpr},
+    ConnectionTrait, DatabaseConnection,
+};
+use sea_orm_pro::{ColumnKind, EntityMeta, FieldError, FilterConditionCfg, FilterOp, WriteMode};
+use seaography::{
+    async_graphql::{self, ServerError},
+    ActiveEnumBuilder,
+};
+use serde_json::Map;
+use std::sync::Arc;
+
+use super::query_root::CONTEXT;
+use crate::admin::{
+    config_store::ConfigStore,
+    virtual_column::{self, sql_value},
+};
+
+/// Mutations writing the `data` argument to the table, by suffix of the mutation name
+const WRITE_MUTATIONS: [(&str, WriteMode); 3] = [
+    ("_create_one", WriteMode::Create),
+    ("_create_batch", WriteMode::Create),
+    ("_update", WriteMode::Update),
+];
+
+/// Reject mutations writing values the admin panel config doesn't accept,
+/// e.g. a value missing in the options of a column or breaking the rules of a field
+pub struct Validation {
+    pub store: Arc<ConfigStore>,
+}
+
+impl ExtensionFactory for Validation {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ValidationExtension {
+            store: self.store.clone(),
+        })
+    }
+}
+
+struct ValidationExtension {
+    store: Arc<ConfigStore>,
+}
+
+#[async_trait::async_trait]
+impl Extension for ValidationExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let mutations = document
+            .operations
+            .iter()
+            .filter(|(_, operation)| matches!(operation.node.ty, OperationType::Mutation));
+        for (_, operation) in mutations {
+            for selection in operation.node.selection_set.node.items.iter() {
+                let Selection::Field(field) = &selection.node else {
+                    continue;
+                };
+                let name = field.node.name.node.as_str();
+                let Some((table, mode)) = WRITE_MUTATIONS
+                    .iter()
+                    .find_map(|(suffix, mode)| Some((name.strip_suffix(suffix)?, *mode)))
+                else {
+                    continue;
+                };
+                let argument = |name: &str| {
+                    field
+                        .node
+                        .get_argument(name)?
+                        .node
+                        .clone()
+                        .into_const_with(|name: Name| variables.get(&name).cloned().ok_or(()))
+                        .ok()?
+                        .into_json()
+                        .ok()
+                };
+                // Rows of a batch are sent as a list
+                let rows: Vec<_> = match argument("data") {
+                    Some(serde_json::Value::Array(rows)) => rows,
+                    Some(row) => vec![row],
+                    None => continue,
+                };
+                let entity = self
+                    .store
+                    .entities()
+                    .iter()
+                    .find(|entity| entity.table_name == table);
+                let rows: Vec<_> = rows
+                    .into_iter()
+                    .filter_map(|row| match row {
+                        serde_json::Value::Object(row) => Some(row),
+                        _ => None,
+                    })
+                    .map(|row| match entity {
+                        Some(entity) => db_values(entity, row),
+                        None => row,
+                    })
+                    .collect();
+
+                let mut errors: Vec<_> = rows
+                    .iter()
+                    .flat_map(|row| self.store.check_input(table, row, mode))
+                    .collect();
+                if let Some(db) = ctx.data_opt::<DatabaseConnection>() {
+                    let filter = argument("filter");
+                    for error in self.check_unique(db, table, &rows, mode, filter).await? {
+                        if !errors.iter().any(|e| e.field == error.field) {
+                            errors.push(error);
+                        }
+                    }
+                }
+                if !errors.is_empty() {
+                    return Err(field_errors(errors, field.pos));
+                }
+            }
+        }
+        Ok(document)
+    }
+}
+
+impl ValidationExtension {
+    /// Look up the values of the unique fields in the table, leaving out the rows being updated,
+    /// i.e. the rows the filter of the update picks. Values repeated in the rows of a batch
+    /// and a unique field set on more than one row at once are rejected as well. Only the values
+    /// repeated in the rows are checked if the filter has an operator not known here.
+    async fn check_unique(
+        &self,
+        db: &DatabaseConnection,
+        table: &str,
+        rows: &[Map<String, serde_json::Value>],
+        mode: WriteMode,
+        filter: Option<serde_json::Value>,
+    ) -> ServerResult<Vec<FieldError>> {
+        let unique_fields = self.store.unique_fields(table);
+        let entity = self
+            .store
+            .entities()
+            .iter()
+            .find(|entity| entity.table_name == table);
+        let (false, Some(entity)) = (unique_fields.is_empty(), entity) else {
+            return Ok(Vec::new());
+        };
+        let mut errors = Vec::new();
+        for field in unique_fields.iter() {
+            let values: Vec<_> = given(rows, field).collect();
+            if values
+                .iter()
+                .enumerate()
+                .any(|(i, value)| values[..i].contains(value))
+            {
+                errors.push(FieldError {
+                    field: field.clone(),
+                    message: "is given more than once".to_owned(),
+                });
+            }
+        }
+
+        let written: Vec<_> = unique_fields
+            .iter()
+            .filter(|field| given(rows, field).next().is_some())
+            .collect();
+        if written.is_empty() {
+            return Ok(errors);
+        }
+        // An update without a filter writes every row
+        let updated_rows = match (mode, &filter) {
+            (WriteMode::Create, _) => None,
+            (WriteMode::Update, Some(filter)) => {
+                match filter_condition(entity, filter, self.store.today()) {
+                    Some(updated_rows) => Some(updated_rows),
+                    // The rows being updated can't be told apart from the others,
+                    // values taken by other rows are left to the unique indexes of the database
+                    None => return Ok(errors),
+                }
+            }
+            (WriteMode::Update, None) => Some(Condition::all()),
+        };
+        if let Some(updated_rows) = &updated_rows {
+            let query = Query::select()
+                .expr(Expr::cust("COUNT(*)"))
+                .from(Alias::new(table))
+                .cond_where(updated_rows.clone())
+                .to_owned();
+            if count(db, &query).await? > 1 {
+                errors.extend(written.into_iter().map(|field| FieldError {
+                    field: field.clone(),
+                    message: "can't be set on more than one row at once".to_owned(),
+                }));
+                return Ok(errors);
+            }
+        }
+        // Rows being updated by the primary key, the filter may be on nullable columns
+        let primary_key: Vec<SimpleExpr> = entity
+            .primary_key()
+            .map(|column| Expr::col(Alias::new(&column.name)).into())
+            .collect();
+        let updated_keys = updated_rows.map(|updated_rows| {
+            let keys = Query::select()
+                .exprs(primary_key.clone())
+                .from(Alias::new(table))
+                .cond_where(updated_rows)
+                .to_owned();
+            match <[_; 1]>::try_from(primary_key) {
+                Ok([key]) => Expr::expr(key).not_in_subquery(keys),
+                Err(primary_key) => Expr::tuple(primary_key).not_in_subquery(keys),
+            }
+        });
+
+        for field in unique_fields {
+            if errors.iter().any(|error| error.field == field) {
+                continue;
+            }
+            let values: Vec<_> = given(rows, &field).filter_map(sql_value).collect();
+            for value in values {
+                let mut cond = Condition::all().add(Expr::col(Alias::new(&field)).eq(value));
+                if let Some(updated_keys) = &updated_keys {
+                    cond = cond.add(updated_keys.clone());
+                }
+                let query = Query::select()
+                    .expr(Expr::cust("COUNT(*)"))
+                    .from(Alias::new(table))
+                    .cond_where(cond)
+                    .to_owned();
+                if count(db, &query).await? > 0 {
+                    errors.push(FieldError {
+                        field,
+                        message: "is already taken".to_owned(),
+                    });
+                    break;
+                }
+            }
+        }
+        Ok(errors)
+    }
+}
+
+/// Values of the field given in the rows
+fn given<'a>(
+    rows: &'a [Map<String, serde_json::Value>],
+    field: &'a str,
+) -> impl Iterator<Item = &'a serde_json::Value> {
+    rows.iter()
+        .filter_map(move |row| row.get(field))
+        .filter(|value| !value.is_null())
+}
+
+/// Number of rows counted by the query
+async fn count(db: &DatabaseConnection, query: &SelectStatement) -> ServerResult<i64> {
+    let stmt = db.get_database_backend().build(query);
+    match db.query_one(stmt).await {
+        Ok(Some(row)) => row.try_get_by_index(0).map_err(server_error),
+        Ok(None) => Ok(0),
+        Err(err) => Err(server_error(err)),
+    }
+}
+
+/// SQL condition of a seaography filter, e.g. `{ id: { is_in: [1, 2] }, or: [...] }`,
+/// `None` if it has a field or an operator not known here
+fn filter_condition(
+    entity: &EntityMeta,
+    filter: &serde_json::Value,
+    today: NaiveDate,
+) -> Option<Condition> {
+    let mut cond = Condition::all();
+    for (key, value) in filter.as_object()? {
+        if let ("and" | "or", serde_json::Value::Array(filters)) = (key.as_str(), value) {
+            let mut group = match key.as_str() {
+                "and" => Condition::all(),
+                _ => Condition::any(),
+            };
+            for filter in filters {
+                group = group.add(filter_condition(entity, filter, today)?);
+            }
+            cond = cond.add(group);
+            continue;
+        }
+        entity.column(key)?;
+        let column = Expr::col(Alias::new(key));
+        for (op, value) in value.as_object()? {
+            // Operators of seaography missing in the filters of the config
+            let condition = match (op.as_str(), value) {
+                ("like", serde_json::Value::String(pattern)) => column.clone().like(pattern),
+                ("not_like", serde_json::Value::String(pattern)) => {
+                    column.clone().not_like(pattern)
+                }
+                ("not_between", serde_json::Value::Array(values)) => {
+                    let values: Vec<_> = values.iter().map(sql_value).collect::<Option<_>>()?;
+                    let [from, to] = <[_; 2]>::try_from(values).ok()?;
+                    column.clone().not_between(from, to)
+                }
+                // The others share their names with the filters of the config
+                (op, value) => {
+                    let condition = FilterConditionCfg {
+                        field: key.clone(),
+                        op: serde_json::from_value::<FilterOp>(op.into()).ok()?,
+                        value: value.clone(),
+                    };
+                    virtual_column::filter_condition(column.clone().into(), &condition, today)
+                        .ok()?
+                }
+            };
+            cond = cond.add(condition);
+        }
+    }
+    Some(cond)
+}
+
+/// Row with the values of enum columns as stored in the database, the options of the config,
+/// GraphQL takes the names of the enum variants given by seaography, e.g. `INPROGRESS`
+fn db_values(
+    entity: &EntityMeta,
+    mut row: Map<String, serde_json::Value>,
+) -> Map<String, serde_json::Value> {
+    let enums = ActiveEnumBuilder { context: &CONTEXT };
+    for column in entity.columns.iter() {
+        let (ColumnKind::Enum, Some(enum_name)) = (column.kind, &column.enum_name) else {
+            continue;
+        };
+        let Some(serde_json::Value::String(variant)) = row.get_mut(&column.name) else {
+            continue;
+        };
+        let value = column
+            .enum_values
+            .iter()
+            .find(|value| enums.variant_name(enum_name, value) == *variant);
+        if let Some(value) = value {
+            *variant = value.clone();
+        }
+    }
+    row
+}
+
+/// Error listing the invalid fields in the `fields` extension, for the form to show next to each field
+fn field_errors(errors: Vec<FieldError>, pos: Pos) -> ServerError {
+    let message = errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut extensions = ErrorExtensionValues::default();
+    let fields = serde_json::to_value(&errors).unwrap_or_default();
+    extensions.set("fields", Value::from_json(fields).unwrap_or_default());
+    let mut error = ServerError::new(message, Some(pos));
+    error.extensions = Some(extensions);
+    error
+}
+
+fn server_error(err: impl std::fmt::Display) -> ServerError {
+    ServerError::new(err.to_string(), None)
+}
@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use axum::{Extension, Router as AxumRouter};
+use loco_rs::{
+    app::{AppContext, Initializer},
+    Result,
+};
+use std::sync::Arc;
+
+use crate::admin::chart_provider::ChartRegistry;
+
+/// Share the chart providers of the app with the dashboard endpoint
+pub struct ChartRegistryInitializer {
+    registry: Arc<ChartRegistry>,
+}
+
+impl ChartRegistryInitializer {
+    pub fn new(registry: ChartRegistry) -> Self {
+        Self {
+            registry: Arc::new(registry),
+        }
+    }
+}
+
+#[async_trait]
+impl Initializer for ChartRegistryInitializer {
+    fn name(&self) -> String {
+        "chart-registry".to_string()
+    }
+
+    async fn after_routes(&self, router: AxumRouter, _ctx: &AppContext) -> Result<AxumRouter> {
+        let router = router.layer(Extension(self.registry.clone()));
+        Ok(router)
+    }
+}
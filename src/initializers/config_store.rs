@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use axum::{Extension, Router as AxumRouter};
+use loco_rs::{
+    app::{AppContext, Initializer},
+    environment::Environment,
+    Error, Result,
+};
+use std::sync::Arc;
+
+use crate::admin::config_store::{ConfigStore, CONFIG_ROOT, EMBEDDED_CONFIG};
+
+pub struct ConfigStoreInitializer;
+
+#[async_trait]
+impl Initializer for ConfigStoreInitializer {
+    fn name(&self) -> String {
+        "config-store".to_string()
+    }
+
+    async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+        // Files in `pro_admin/overlays/<environment>/` are merged over the base config
+        let environment = ctx.environment.to_string();
+        let store = if ctx.environment == Environment::Production {
+            // Ship as a single binary, the config folder is optional and only overrides files
+            Arc::new(ConfigStore::with_embedded(
+                &EMBEDDED_CONFIG,
+                CONFIG_ROOT,
+                environment,
+            ))
+        } else {
+            Arc::new(ConfigStore::new(CONFIG_ROOT, environment))
+        };
+
+        if ctx.environment == Environment::Production {
+            // Refuse to boot with a broken admin panel config,
+            // edited config is picked up with the reload endpoint afterwards
+            if let Some(err) = store.error() {
+                return Err(Error::Message(err.to_string()));
+            }
+        } else {
+            if let Some(err) = store.error() {
+                tracing::error!("{err}");
+            }
+            // Pick up changes of the config on the fly
+            if let Err(err) = store.watch() {
+                tracing::warn!("cannot watch admin panel config in `{CONFIG_ROOT}`: {err}");
+            }
+        }
+
+        let router = router.layer(Extension(store));
+        Ok(router)
+    }
+}
@@ -1 +1,3 @@
 pub mod casbin_enforcer;
+pub mod chart_registry;
+pub mod config_store;
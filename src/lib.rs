@@ -1,4 +1,6 @@
+pub mod admin;
 pub mod app;
+pub mod charts;
 pub mod controllers;
 pub mod graphql;
 pub mod initializers;
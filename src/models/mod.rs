@@ -19,6 +19,7 @@ pub mod product_model;
 pub mod product_model_product_description;
 pub mod sales_order_detail;
 pub mod sales_order_header;
+pub mod saved_filter;
 pub mod user;
 
 seaography::register_entity_modules!([
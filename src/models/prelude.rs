@@ -15,4 +15,5 @@ pub use super::product_model::Entity as ProductModel;
 pub use super::product_model_product_description::Entity as ProductModelProductDescription;
 pub use super::sales_order_detail::Entity as SalesOrderDetail;
 pub use super::sales_order_header::Entity as SalesOrderHeader;
+pub use super::saved_filter::Entity as SavedFilter;
 pub use super::user::Entity as User;
@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Filter of a table saved by a user of the admin panel, not exposed in the GraphQL schema
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "saved_filter")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[serde(skip)]
+    pub user_id: i32,
+    pub table_name: String,
+    pub name: String,
+    /// Conditions of the filter, see [`sea_orm_pro::FilterConditionCfg`]
+    pub conditions: Json,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
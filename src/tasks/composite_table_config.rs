@@ -0,0 +1,76 @@
+//! This task generates a composite table config of a parent table,
+//! with a child table for each relation of the parent entity.
+//!
+//! # Example
+//!
+//! Write `pro_admin/composite_tables/sales_order_header.toml`:
+//! ```sh
+//! cargo run task generate_composite_table parent:sales_order_header
+//! ```
+//!
+//! To name the composite table, and overwrite its existing config,
+//! use the `name` and `force:true` arguments:
+//! ```sh
+//! cargo run task generate_composite_table parent:sales_order_header name:sales_order force:true
+//! ```
+
+use loco_rs::prelude::*;
+use sea_orm_pro::{CompositeTableCfg, ConfigFormat};
+use std::path::Path;
+
+use crate::admin::{config_store::CONFIG_ROOT, entities::entity_metas};
+
+pub struct GenerateCompositeTable;
+#[async_trait]
+impl Task for GenerateCompositeTable {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "generate_composite_table".to_string(),
+            detail: "Generate composite table config from the relations of an entity".to_string(),
+        }
+    }
+
+    async fn run(&self, _app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let force = vars.cli_arg("force").is_ok_and(|force| force == "true");
+        let parent = vars.cli_arg("parent")?;
+        let name = vars.cli_arg("name").unwrap_or(parent);
+
+        let entities = entity_metas();
+        let Some(entity) = entities.iter().find(|entity| entity.table_name == *parent) else {
+            return Err(Error::Message(format!(
+                "table `{parent}` is not registered in the admin panel"
+            )));
+        };
+
+        let folder = Path::new(CONFIG_ROOT).join("composite_tables");
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(format!("{name}.toml"));
+        // Config of the table may be written in any of the supported formats
+        let existing = ConfigFormat::EXTENSIONS
+            .iter()
+            .map(|ext| folder.join(format!("{name}.{ext}")))
+            .find(|path| path.is_file());
+        if let Some(existing) = existing {
+            if !force {
+                println!("Skipped: {existing:?} already exists");
+                return Ok(());
+            }
+            std::fs::remove_file(existing)?;
+        }
+
+        let config = CompositeTableCfg::generate(entity, &entities);
+        for child in config.children.iter() {
+            println!("Child table: {}", child.relation);
+        }
+        let config = config
+            .to_toml()
+            .map_err(|err| Error::Message(err.to_string()))?;
+        let content = format!(
+            "# Generated from the relations of the `{parent}` entity, edit to fit your needs\n\n{config}"
+        );
+        std::fs::write(&path, content)?;
+        println!("Generated: {path:?}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! This task writes the JSON Schema of the admin panel config files,
+//! editors such as VS Code with Even Better TOML (taplo) use them to
+//! validate and autocomplete the config, see `.taplo.toml`.
+//!
+//! # Example
+//!
+//! Write the schemas to `pro_admin/schema/`:
+//! ```sh
+//! cargo run task export_config_schema
+//! ```
+//!
+//! To write the schemas to another folder, use the `dir` argument:
+//! ```sh
+//! cargo run task export_config_schema dir:schema
+//! ```
+
+use loco_rs::prelude::*;
+use sea_orm_pro::ConfigSchema;
+use std::path::PathBuf;
+
+use crate::admin::config_store::CONFIG_ROOT;
+
+pub struct ExportConfigSchema;
+#[async_trait]
+impl Task for ExportConfigSchema {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "export_config_schema".to_string(),
+            detail: "Write JSON Schema of the admin panel config files".to_string(),
+        }
+    }
+
+    async fn run(&self, _app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let folder = match vars.cli_arg("dir") {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => PathBuf::from(CONFIG_ROOT).join("schema"),
+        };
+        std::fs::create_dir_all(&folder)?;
+
+        for schema in ConfigSchema::ALL {
+            let path = folder.join(schema.file_name());
+            let content = serde_json::to_string_pretty(&schema.schema())?;
+            std::fs::write(&path, content + "\n")?;
+            println!("Exported: {path:?}");
+        }
+
+        Ok(())
+    }
+}
@@ -1 +1,4 @@
+pub mod composite_table_config;
+pub mod config_schema;
+pub mod raw_table_config;
 pub mod seed;
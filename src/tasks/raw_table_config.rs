@@ -0,0 +1,79 @@
+//! This task generates the default raw table config of every entity
+//! registered in the admin panel.
+//!
+//! # Example
+//!
+//! Write `pro_admin/raw_tables/<table>.toml` for tables without config:
+//! ```sh
+//! cargo run task generate_raw_tables
+//! ```
+//!
+//! To generate a single table, and overwrite its existing config,
+//! use the `table` and `force:true` arguments:
+//! ```sh
+//! cargo run task generate_raw_tables table:cake force:true
+//! ```
+
+use loco_rs::prelude::*;
+use sea_orm_pro::{ConfigFormat, RawTableCfg};
+use std::path::Path;
+
+use crate::admin::{config_store::CONFIG_ROOT, entities::entity_metas};
+
+pub struct GenerateRawTables;
+#[async_trait]
+impl Task for GenerateRawTables {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "generate_raw_tables".to_string(),
+            detail: "Generate default raw table config from the entities".to_string(),
+        }
+    }
+
+    async fn run(&self, _app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let force = vars.cli_arg("force").is_ok_and(|force| force == "true");
+        let table = vars.cli_arg("table").ok();
+
+        let folder = Path::new(CONFIG_ROOT).join("raw_tables");
+        std::fs::create_dir_all(&folder)?;
+
+        let entities: Vec<_> = entity_metas()
+            .into_iter()
+            .filter(|entity| table.is_none_or(|table| *table == entity.table_name))
+            .collect();
+        if entities.is_empty() {
+            return Err(Error::Message(format!(
+                "table `{}` is not registered in the admin panel",
+                table.map(String::as_str).unwrap_or_default()
+            )));
+        }
+
+        for entity in entities {
+            let path = folder.join(format!("{}.toml", entity.table_name));
+            // Config of the table may be written in any of the supported formats
+            let existing = ConfigFormat::EXTENSIONS
+                .iter()
+                .map(|ext| folder.join(format!("{}.{ext}", entity.table_name)))
+                .find(|path| path.is_file());
+            if let Some(existing) = existing {
+                if !force {
+                    println!("Skipped: {existing:?} already exists");
+                    continue;
+                }
+                std::fs::remove_file(existing)?;
+            }
+
+            let config = RawTableCfg::generate(&entity)
+                .to_toml()
+                .map_err(|err| Error::Message(err.to_string()))?;
+            let content = format!(
+                "# Generated from the `{}` entity, edit to fit your needs\n\n{config}",
+                entity.table_name
+            );
+            std::fs::write(&path, content)?;
+            println!("Generated: {path:?}");
+        }
+
+        Ok(())
+    }
+}